@@ -0,0 +1,326 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tetris::engine::{DigGarbage, HEIGHT_RANGE, WIDTH_RANGE, Kicks180, LockReset, Mode, ModeKind, PieceSet, Ruleset, SpeedCurve, StackVisibility, RandomizerKind, RotationKind, ScoringKind, TopOut, TopOutRules, MAX_PREVIEWS};
+use crate::resources::{GameSettings, Handling, KeyBindings};
+
+/// `--mode <marathon|endless|sprint|ultra|dig|master>` (applied first wherever it appears, as it picks the mode's rules) `--start-level <n>` `--goal <lines>`
+/// `--time-limit <seconds>` `--dig-height <rows>` `--dig-lines <rows>` `--hole-change <0-1>`
+/// `--stack <visible|fading|invisible>` `--fade-delay <ms>` `--big <on|off>`
+/// `--randomizer <random|7bag|14bag|tgm|nes>` `--seed <u64>` `--previews <1-6>`
+/// `--lock-delay <ms>` `--lock-reset <move|step|none>` `--lock-resets <n>` `--bind-sonic-drop <key>`
+/// `--das <ms>` `--arr <ms>` `--sdf <factor|inf>` `--kicks-180 <none|srs+>` `--scoring <guideline|nes|tgm>`
+/// `--width <4-20>` `--height <10-40>` `--buffer-rows <n>` `--top-out <block,lock,partial,garbage>` `--rotation <srs|ars|nrs>`
+/// `--irs <on|off>` `--ihs <on|off>` `--fresh-initial <on|off>` `--are <ms>` `--line-clear-delay <ms>` `--speed <guideline|master>`
+/// `--pieces <tetromino|pentomino|mini|path.ron>`
+pub fn parse_args(args: impl Iterator<Item = String>) -> (GameSettings, KeyBindings, Handling) {
+    let mut settings = GameSettings::default();
+    let mut bindings = KeyBindings::default();
+    let mut handling = Handling::default();
+    // The mode replaces the whole ruleset, so take it out and apply it before anything it would undo
+    let mut args: Vec<String> = args.collect();
+    while let Some(index) = args.iter().position(|arg| arg == "--mode") {
+        let value: String = args.drain(index..(index + 2).min(args.len())).nth(1).unwrap_or_default();
+        match ModeKind::from_name(&value) {
+            Some(kind) => settings.ruleset = Ruleset::for_mode(Mode::new(kind)),
+            None => eprintln!("Unknown mode '{}', using {}", value, settings.ruleset.mode.kind.name()),
+        }
+    }
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start-level" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<u32>() {
+                    // Line-based modes start at level 1 at the lowest
+                    Ok(level) => settings.ruleset.mode.start_level = level,
+                    _ => eprintln!("Invalid start level '{}'", value),
+                }
+            }
+            "--goal" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<u32>() {
+                    Ok(lines) if lines >= 1 => settings.ruleset.mode.line_goal = Some(lines),
+                    _ => eprintln!("Invalid line goal '{}'", value),
+                }
+            }
+            "--time-limit" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<u64>() {
+                    Ok(seconds) if seconds >= 1 => settings.ruleset.mode.time_limit = Some(Duration::from_secs(seconds)),
+                    _ => eprintln!("Invalid time limit '{}', expected seconds", value),
+                }
+            }
+            "--dig-height" | "--dig-lines" | "--hole-change" => {
+                let value = args.next().unwrap_or_default();
+                let dig = settings.ruleset.mode.dig.get_or_insert_with(DigGarbage::default);
+                let valid = match arg.as_str() {
+                    "--dig-height" => value.parse().map(|rows| dig.height = rows).is_ok(),
+                    "--dig-lines" => value.parse().map(|rows| dig.total = rows).is_ok(),
+                    _ => match value.parse::<f32>() {
+                        Ok(chance) if (0.0..=1.0).contains(&chance) => {
+                            dig.hole_change = chance;
+                            true
+                        }
+                        _ => false,
+                    },
+                };
+                if !valid {
+                    eprintln!("Invalid value '{}' for {}", value, arg);
+                }
+            }
+            "--stack" => {
+                let value = args.next().unwrap_or_default();
+                match StackVisibility::from_name(&value) {
+                    Some(stack) => settings.ruleset.mode.stack = stack,
+                    None => eprintln!("Unknown stack visibility '{}', using {}", value, settings.ruleset.mode.stack.name()),
+                }
+            }
+            "--fade-delay" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    // Implies a fading stack
+                    Ok(ms) => settings.ruleset.mode.stack = StackVisibility::Fading { delay: Duration::from_millis(ms) },
+                    Err(_) => eprintln!("Invalid fade delay '{}', expected milliseconds", value),
+                }
+            }
+            "--speed" => {
+                let value = args.next().unwrap_or_default();
+                match value.as_str() {
+                    "guideline" => settings.ruleset.speed = SpeedCurve::default(),
+                    "master" => settings.ruleset.speed = SpeedCurve::master(),
+                    _ => eprintln!("Unknown speed curve '{}'", value),
+                }
+            }
+            "--randomizer" => {
+                let value = args.next().unwrap_or_default();
+                match RandomizerKind::from_name(&value) {
+                    Some(kind) => settings.ruleset.randomizer = kind,
+                    None => eprintln!("Unknown randomizer '{}', using {}", value, settings.ruleset.randomizer.name()),
+                }
+            }
+            "--seed" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(seed) => settings.seed = Some(seed),
+                    Err(_) => eprintln!("Invalid seed '{}', using a random one", value),
+                }
+            }
+            "--previews" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<usize>() {
+                    Ok(previews) if (1..=MAX_PREVIEWS).contains(&previews) => settings.ruleset.previews = previews,
+                    _ => eprintln!("Invalid preview count '{}', expected 1-{}", value, MAX_PREVIEWS),
+                }
+            }
+            "--lock-delay" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(ms) => settings.ruleset.lock_delay = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid lock delay '{}', expected milliseconds", value),
+                }
+            }
+            "--are" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(ms) => settings.ruleset.are = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid ARE '{}', expected milliseconds", value),
+                }
+            }
+            "--line-clear-delay" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(ms) => settings.ruleset.line_clear_delay = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid line clear delay '{}', expected milliseconds", value),
+                }
+            }
+            "--lock-reset" => {
+                let value = args.next().unwrap_or_default();
+                match LockReset::from_name(&value) {
+                    Some(policy) => settings.ruleset.lock_reset = policy,
+                    None => eprintln!("Unknown lock reset '{}', using {}", value, settings.ruleset.lock_reset.name()),
+                }
+            }
+            "--lock-resets" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(limit) => settings.ruleset.max_lock_resets = limit,
+                    Err(_) => eprintln!("Invalid lock reset limit '{}'", value),
+                }
+            }
+            "--rotation" => {
+                let value = args.next().unwrap_or_default();
+                match RotationKind::from_name(&value) {
+                    Some(kind) => settings.ruleset.rotation = kind,
+                    None => eprintln!("Unknown rotation system '{}', using {}", value, settings.ruleset.rotation.name()),
+                }
+            }
+            "--irs" | "--ihs" | "--fresh-initial" | "--big" => {
+                let value = args.next().unwrap_or_default();
+                let enabled = match value.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    _ => {
+                        eprintln!("Invalid value '{}' for {}, expected on or off", value, arg);
+                        continue;
+                    }
+                };
+                match arg.as_str() {
+                    "--irs" => settings.ruleset.irs = enabled,
+                    "--ihs" => settings.ruleset.ihs = enabled,
+                    "--fresh-initial" => settings.ruleset.fresh_initial = enabled,
+                    _ => settings.ruleset.mode.big = enabled,
+                }
+            }
+            "--kicks-180" => {
+                let value = args.next().unwrap_or_default();
+                match Kicks180::from_name(&value) {
+                    Some(table) => settings.ruleset.kicks_180 = table,
+                    None => eprintln!("Unknown 180 kick table '{}', using {}", value, settings.ruleset.kicks_180.name()),
+                }
+            }
+            "--scoring" => {
+                let value = args.next().unwrap_or_default();
+                match ScoringKind::from_name(&value) {
+                    Some(kind) => settings.ruleset.scoring = kind,
+                    None => eprintln!("Unknown scoring '{}', using {}", value, settings.ruleset.scoring.name()),
+                }
+            }
+            "--width" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<i32>() {
+                    Ok(width) if WIDTH_RANGE.contains(&width) => settings.ruleset.width = width,
+                    _ => eprintln!("Invalid board width '{}', expected {} to {}", value, WIDTH_RANGE.start(), WIDTH_RANGE.end()),
+                }
+            }
+            "--height" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<i32>() {
+                    Ok(height) if HEIGHT_RANGE.contains(&height) => settings.ruleset.height = height,
+                    _ => eprintln!("Invalid board height '{}', expected {} to {}", value, HEIGHT_RANGE.start(), HEIGHT_RANGE.end()),
+                }
+            }
+            "--buffer-rows" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<i32>() {
+                    Ok(rows) if rows >= 0 => settings.ruleset.buffer_rows = rows,
+                    _ => eprintln!("Invalid buffer row count '{}'", value),
+                }
+            }
+            "--pieces" => {
+                // A bundled set by name, otherwise a path to a RON file
+                let value = args.next().unwrap_or_default();
+                let set = match PieceSet::bundled(&value) {
+                    Some(set) => Ok(set),
+                    None => PieceSet::load(&value),
+                };
+                match set {
+                    Ok(set) => settings.ruleset.pieces = set,
+                    Err(err) => eprintln!("Could not load piece set '{}': {}, using {}", value, err, settings.ruleset.pieces.name),
+                }
+            }
+            "--top-out" => {
+                // Comma-separated list of the conditions that end the game
+                let value = args.next().unwrap_or_default();
+                let mut rules = TopOutRules::NONE;
+                let mut valid = true;
+                for name in value.split(',').filter(|name| !name.is_empty()) {
+                    match TopOut::from_name(name) {
+                        Some(reason) => rules.set(reason, true),
+                        None => valid = false,
+                    }
+                }
+                if valid {
+                    settings.ruleset.top_out = rules;
+                } else {
+                    eprintln!("Invalid top-out list '{}', expected some of block,lock,partial,garbage", value);
+                }
+            }
+            "--das" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(ms) => handling.das = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid DAS '{}', expected milliseconds", value),
+                }
+            }
+            "--arr" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(ms) => handling.arr = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid ARR '{}', expected milliseconds", value),
+                }
+            }
+            "--sdf" => {
+                // "inf" parses as f32::INFINITY
+                let value = args.next().unwrap_or_default();
+                match value.parse::<f32>() {
+                    Ok(factor) if factor > 0.0 => handling.soft_drop_factor = factor,
+                    _ => eprintln!("Invalid soft drop factor '{}'", value),
+                }
+            }
+            "--bind-sonic-drop" => {
+                let value = args.next().unwrap_or_default();
+                match parse_key(&value) {
+                    Some(key) => bindings.sonic_drop = vec![key],
+                    None => eprintln!("Unknown key '{}'", value),
+                }
+            }
+            _ => eprintln!("Ignoring unknown argument '{}'", arg),
+        }
+    }
+    (settings, bindings, handling)
+}
+
+/// Single letters and digits, plus a few named keys (`Space`, `Up`, `Tab`, ...).
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Space,
+        "up" => KeyCode::ArrowUp,
+        "down" => KeyCode::ArrowDown,
+        "left" => KeyCode::ArrowLeft,
+        "right" => KeyCode::ArrowRight,
+        "tab" => KeyCode::Tab,
+        "enter" => KeyCode::Enter,
+        "shift" => KeyCode::ShiftLeft,
+        "ctrl" => KeyCode::ControlLeft,
+        "alt" => KeyCode::AltLeft,
+        "a" => KeyCode::KeyA,
+        "b" => KeyCode::KeyB,
+        "c" => KeyCode::KeyC,
+        "d" => KeyCode::KeyD,
+        "e" => KeyCode::KeyE,
+        "f" => KeyCode::KeyF,
+        "g" => KeyCode::KeyG,
+        "h" => KeyCode::KeyH,
+        "i" => KeyCode::KeyI,
+        "j" => KeyCode::KeyJ,
+        "k" => KeyCode::KeyK,
+        "l" => KeyCode::KeyL,
+        "m" => KeyCode::KeyM,
+        "n" => KeyCode::KeyN,
+        "o" => KeyCode::KeyO,
+        "p" => KeyCode::KeyP,
+        "q" => KeyCode::KeyQ,
+        "r" => KeyCode::KeyR,
+        "s" => KeyCode::KeyS,
+        "t" => KeyCode::KeyT,
+        "u" => KeyCode::KeyU,
+        "v" => KeyCode::KeyV,
+        "w" => KeyCode::KeyW,
+        "x" => KeyCode::KeyX,
+        "y" => KeyCode::KeyY,
+        "z" => KeyCode::KeyZ,
+        "0" => KeyCode::Digit0,
+        "1" => KeyCode::Digit1,
+        "2" => KeyCode::Digit2,
+        "3" => KeyCode::Digit3,
+        "4" => KeyCode::Digit4,
+        "5" => KeyCode::Digit5,
+        "6" => KeyCode::Digit6,
+        "7" => KeyCode::Digit7,
+        "8" => KeyCode::Digit8,
+        "9" => KeyCode::Digit9,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,172 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use super::piece::PieceType;
+
+pub const DEFAULT_WIDTH: i32 = 10;
+// Visible rows
+pub const DEFAULT_HEIGHT: i32 = 20;
+// Board sizes a ruleset may ask for; anything outside is clamped
+pub const WIDTH_RANGE: RangeInclusive<i32> = 4..=20;
+pub const HEIGHT_RANGE: RangeInclusive<i32> = 10..=40;
+// Hidden rows above the visible ones, as in the guideline
+pub const BUFFER_ROWS: i32 = 20;
+
+/// What fills an occupied board cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Piece(PieceType),
+    Garbage,
+}
+
+type Row = Vec<Option<Cell>>;
+
+/// The locked stack. Row 0 is the bottom of the playfield; rows from `visible_height` up
+/// are the hidden buffer, where pieces spawn and where the stack can grow unseen.
+#[derive(Clone, Debug)]
+pub struct Board {
+    width: i32,
+    visible_height: i32,
+    // Stores what a locked cell came from, or None if empty.
+    pub cells: Vec<Row>,
+    // Play time each cell was filled at, row for row with `cells`
+    filled_at: Vec<Vec<Duration>>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(DEFAULT_WIDTH, DEFAULT_HEIGHT, BUFFER_ROWS)
+    }
+}
+
+impl Board {
+    /// `width` by `visible_height` cells (clamped to the supported sizes) with `buffer_rows` hidden above.
+    pub fn new(width: i32, visible_height: i32, buffer_rows: i32) -> Self {
+        let width = width.clamp(*WIDTH_RANGE.start(), *WIDTH_RANGE.end());
+        let visible_height = visible_height.clamp(*HEIGHT_RANGE.start(), *HEIGHT_RANGE.end());
+        let height = (visible_height + buffer_rows.max(0)) as usize;
+        Self {
+            width,
+            visible_height,
+            cells: vec![vec![None; width as usize]; height],
+            filled_at: vec![vec![Duration::ZERO; width as usize]; height],
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Rows on show, below the buffer.
+    pub fn visible_height(&self) -> i32 {
+        self.visible_height
+    }
+
+    /// Visible and buffer rows together.
+    pub fn height(&self) -> i32 {
+        self.cells.len() as i32
+    }
+
+    /// Pivot row for new pieces made of `scale`-cell blocks: just above the visible area, or
+    /// as high as fits if the buffer is smaller than that, on a whole block.
+    pub fn spawn_row(&self, scale: i32) -> i32 {
+        self.visible_height.min(self.height() - 2 * scale) / scale * scale
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<Cell> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.cells[y as usize][x as usize]
+    }
+
+    /// Play time when the block at (x, y) locked or rose in as garbage, or None if the cell is empty.
+    pub fn filled_at(&self, x: i32, y: i32) -> Option<Duration> {
+        self.get(x, y).map(|_| self.filled_at[y as usize][x as usize])
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height()).contains(&y)
+    }
+
+    /// True if (x, y) is inside the board and not occupied.
+    pub fn is_valid_position(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && self.cells[y as usize][x as usize].is_none()
+    }
+
+    pub fn fits(&self, cells: &[(i32, i32)]) -> bool {
+        cells.iter().all(|&(x, y)| self.is_valid_position(x, y))
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, cell: Cell) {
+        self.set_at(x, y, cell, Duration::ZERO);
+    }
+
+    /// `set`, recording that it happened at play time `time`.
+    pub fn set_at(&mut self, x: i32, y: i32, cell: Cell, time: Duration) {
+        if self.in_bounds(x, y) {
+            self.cells[y as usize][x as usize] = Some(cell);
+            self.filled_at[y as usize][x as usize] = time;
+        }
+    }
+
+    /// Pushes the stack up one row and fills the bottom with garbage, leaving a gap at `hole`.
+    /// True if that pushed any block off the top of the buffer.
+    pub fn push_garbage(&mut self, hole: i32, time: Duration) -> bool {
+        let overflowed = self.cells.last().is_some_and(|row| row.iter().any(|cell| cell.is_some()));
+        let mut row = vec![Some(Cell::Garbage); self.width as usize];
+        if (0..self.width).contains(&hole) {
+            row[hole as usize] = None;
+        }
+        self.cells.pop();
+        self.cells.insert(0, row);
+        self.filled_at.pop();
+        self.filled_at.insert(0, vec![time; self.width as usize]);
+        overflowed
+    }
+
+    /// True if every row not listed in `rows` is empty.
+    pub fn is_empty_except(&self, rows: &[i32]) -> bool {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(y, _)| !rows.contains(&(*y as i32)))
+            .all(|(_, row)| row.iter().all(|cell| cell.is_none()))
+    }
+
+    pub fn is_garbage_row(&self, y: i32) -> bool {
+        self.in_bounds(0, y) && self.cells[y as usize].contains(&Some(Cell::Garbage))
+    }
+
+    fn row_full(&self, y: usize) -> bool {
+        self.cells[y].iter().all(|cell| cell.is_some())
+    }
+
+    /// Indices of the full rows, bottom first.
+    pub fn full_rows(&self) -> Vec<i32> {
+        (0..self.cells.len())
+            .filter(|&y| self.row_full(y))
+            .map(|y| y as i32)
+            .collect()
+    }
+
+    /// Removes every full row, shifting the rows above down.
+    /// Returns the indices of the cleared rows (as they were before shifting), bottom first.
+    pub fn clear_full_rows(&mut self) -> Vec<i32> {
+        let mut cleared = Vec::new();
+        let mut write = 0;
+        for read in 0..self.cells.len() {
+            if self.row_full(read) {
+                cleared.push(read as i32);
+                continue;
+            }
+            self.cells.swap(write, read);
+            self.filled_at.swap(write, read);
+            write += 1;
+        }
+        for row in &mut self.cells[write..] {
+            row.fill(None);
+        }
+        cleared
+    }
+}
@@ -0,0 +1,1369 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::board::{Board, Cell};
+use super::grade::{Grade, Grading};
+use super::gravity::{self, MAX_GRAVITY};
+use super::piece::PieceType;
+use super::randomizer::Randomizer;
+use super::mode::{DigGarbage, Ending, Leveling};
+use super::rotation::RotationKind;
+use super::ruleset::{LockReset, Ruleset, TopOut, MAX_PREVIEWS};
+use super::scoring::{LineClear, Scorer};
+use super::speed::Delays;
+use super::spin::{self, Kick, TSpin};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActivePiece {
+    pub piece_type: PieceType,
+    pub x: i32, // Pivot in grid coordinates
+    pub y: i32,
+    pub rotation_state: usize, // 0, 1, 2, 3
+    pub rotation_system: RotationKind,
+    // Board cells per block side: 1, or 2 in Big mode, where the piece also moves in steps of 2
+    pub scale: i32,
+}
+
+impl ActivePiece {
+    /// A new piece in spawn orientation at the top of `board`.
+    pub fn spawn(piece_type: PieceType, board: &Board, rotation_system: RotationKind, scale: i32) -> Self {
+        let (dx, dy) = piece_type.spawn_offset();
+        // Centred on the field as it is in blocks, then moved as the piece asks, then pulled
+        // back in wherever that would hang over a wall (narrow boards, wide pieces)
+        let columns = board.width() / scale;
+        let offsets = rotation_system.system().offsets(piece_type, 0);
+        let left = offsets.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let right = offsets.iter().map(|&(x, _)| x).max().unwrap_or(0);
+        let x = (columns / 2 + dx).min(columns - 1 - right).max(-left);
+        Self {
+            piece_type,
+            x: x * scale,
+            y: board.spawn_row(scale) + dy * scale,
+            rotation_state: 0,
+            rotation_system,
+            scale,
+        }
+    }
+
+    /// Where each block's bottom-left cell is; a block covers `scale`×`scale` cells from there.
+    pub fn blocks(&self) -> Vec<(i32, i32)> {
+        self.rotation_system
+            .system()
+            .offsets(self.piece_type, self.rotation_state)
+            .into_iter()
+            .map(|(x, y)| (self.x + x * self.scale, self.y + y * self.scale))
+            .collect()
+    }
+
+    /// Board cells covered by the piece.
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        let scale = self.scale;
+        self.blocks()
+            .into_iter()
+            .flat_map(|(x, y)| (0..scale * scale).map(move |i| (x + i % scale, y + i / scale)))
+            .collect()
+    }
+
+    fn moved(&self, dx: i32, dy: i32) -> Self {
+        Self { x: self.x + dx, y: self.y + dy, ..*self }
+    }
+}
+
+/// What the player asked for this frame. Auto-repeat is resolved by the caller,
+/// so movement arrives as cell counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Inputs {
+    // Blocks to shift sideways (2 cells each in Big mode), negative is left; the piece stops at the first obstacle
+    pub shift: i32,
+    // Blocks to soft drop
+    pub soft_drop: u32,
+    pub hard_drop: bool,
+    pub sonic_drop: bool,
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub rotate_180: bool,
+    pub hold: bool,
+    // Buttons down this frame, pressed now or earlier; drives initial rotation and hold
+    pub held: HeldButtons,
+}
+
+/// Buttons that act on a piece as it spawns (IRS/IHS) if they are down at that moment.
+/// With `Ruleset::fresh_initial`, only presses made after the previous piece locked count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeldButtons {
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub rotate_180: bool,
+    pub hold: bool,
+}
+
+impl HeldButtons {
+    /// The buttons `inputs` pressed this frame.
+    fn pressed(inputs: &Inputs) -> Self {
+        Self {
+            rotate_cw: inputs.rotate_cw,
+            rotate_ccw: inputs.rotate_ccw,
+            rotate_180: inputs.rotate_180,
+            hold: inputs.hold,
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        Self {
+            rotate_cw: self.rotate_cw || other.rotate_cw,
+            rotate_ccw: self.rotate_ccw || other.rotate_ccw,
+            rotate_180: self.rotate_180 || other.rotate_180,
+            hold: self.hold || other.hold,
+        }
+    }
+
+    fn and(self, other: Self) -> Self {
+        Self {
+            rotate_cw: self.rotate_cw && other.rotate_cw,
+            rotate_ccw: self.rotate_ccw && other.rotate_ccw,
+            rotate_180: self.rotate_180 && other.rotate_180,
+            hold: self.hold && other.hold,
+        }
+    }
+}
+
+/// Everything observable that happened during a `Game::step`, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    Spawned(PieceType),
+    Moved,
+    Rotated,
+    // Blocks travelled; a hard drop is followed by `Locked`, a sonic drop is not
+    HardDropped(u32),
+    SonicDropped(u32),
+    Held(PieceType),
+    Locked { t_spin: TSpin },
+    // Scored as the piece locks; the rows stay on the board through the line-clear delay
+    LinesCleared(u32),
+    // The cleared rows were removed and the stack above them dropped
+    LinesCollapsed,
+    LevelUp(u32),
+    // Rows of garbage pushed in from the bottom
+    GarbageRaised(u32),
+    GameOver(Ending),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub score: u32,
+    pub level: u32,
+    pub lines_cleared: u32,
+    // Pieces locked so far
+    pub pieces: u32,
+    // Consecutive locks that cleared lines; 0 once a lock clears nothing
+    pub combo: u32,
+    // The last line clear was a tetris or spin, so the next one can be back-to-back
+    pub back_to_back: bool,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            score: 0,
+            level: 1,
+            lines_cleared: 0,
+            pieces: 0,
+            combo: 0,
+            back_to_back: false,
+        }
+    }
+}
+
+/// What the game is doing between pieces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// A piece is in play.
+    Falling,
+    /// Full rows are on show before they are removed.
+    LineClear { remaining: Duration },
+    /// Entry delay (ARE): the next piece is withheld.
+    Entry { remaining: Duration },
+}
+
+/// A single game of Tetris, independent of any rendering or input backend.
+#[derive(Clone, Debug)]
+pub struct Game {
+    ruleset: Ruleset,
+    seed: u64,
+    rng: ChaCha8Rng,
+    randomizer: Box<dyn Randomizer>,
+    // The set's pieces, which the randomizer picks from
+    pieces: Vec<PieceType>,
+    scorer: Box<dyn Scorer>,
+    board: Board,
+    active: Option<ActivePiece>,
+    phase: Phase,
+    // Rows waiting out the line-clear delay
+    clearing_rows: Vec<i32>,
+    next_queue: VecDeque<PieceType>,
+    hold: Option<PieceType>,
+    can_hold: bool,
+    score: Score,
+    // Fraction of a cell gravity has built up towards the next drop
+    gravity_progress: f32,
+    // Blocks the player has dropped the active piece by, soft, sonic or hard
+    dropped: u32,
+    // Lines cleared over the current combo
+    combo_lines: u32,
+    lock_state: LockState,
+    // Kick index of the last successful move, if that move was a rotation
+    last_kick: Option<Kick>,
+    // Buttons down this frame
+    held: HeldButtons,
+    // Of those, the ones pressed while no piece was in play (for `Ruleset::fresh_initial`)
+    armed: HeldButtons,
+    dig: Option<DigState>,
+    grading: Option<Grading>,
+    // Play time so far, once the clock is running
+    elapsed: Duration,
+    clock_running: bool,
+    // Play time at each split the mode asks for, or at each section in sectioned modes
+    splits: Vec<Duration>,
+    ending: Option<Ending>,
+}
+
+/// Dig mode progress. Garbage has its own RNG so the piece sequence matches other modes on the same seed.
+#[derive(Clone, Debug)]
+struct DigState {
+    config: DigGarbage,
+    rng: ChaCha8Rng,
+    hole: Option<i32>,
+    added: u32,
+    cleared: u32,
+}
+
+impl DigState {
+    // Mixed into the game seed for the garbage RNG
+    const SEED_SALT: u64 = 0x6469_6767_6172_6261;
+
+    fn next_hole(&mut self, width: i32) -> i32 {
+        let hole = match self.hole {
+            Some(hole) if !self.rng.random_bool(self.config.hole_change.clamp(0.0, 1.0) as f64) => hole,
+            // Any other column
+            Some(hole) => (hole + self.rng.random_range(1..width)) % width,
+            None => self.rng.random_range(0..width),
+        };
+        self.hole = Some(hole);
+        self.added += 1;
+        hole
+    }
+}
+
+/// Lock-delay bookkeeping for the active piece.
+#[derive(Clone, Copy, Debug, Default)]
+struct LockState {
+    elapsed: Duration,
+    resets: u32,
+    lowest_y: i32,
+    grounded: bool,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Ruleset::default(), random_seed())
+    }
+}
+
+impl Game {
+    /// The same `ruleset` and `seed` always produce the same piece sequence, on every platform:
+    /// ChaCha8 is a fixed algorithm, unlike `StdRng`, which may change between `rand` releases.
+    pub fn new(ruleset: Ruleset, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut randomizer = ruleset.randomizer.build();
+        let previews = ruleset.previews.clamp(1, MAX_PREVIEWS);
+        let pieces = ruleset.pieces.types();
+        let next_queue = (0..previews).map(|_| randomizer.next_piece(&mut rng, &pieces)).collect();
+        let scorer = ruleset.scoring.build();
+        let start_level = ruleset.mode.first_level();
+        let board = ruleset.board();
+        let clock_running = !ruleset.mode.clock_starts_on_input;
+        let dig = ruleset.mode.dig.map(|config| DigState {
+            config,
+            rng: ChaCha8Rng::seed_from_u64(seed ^ DigState::SEED_SALT),
+            hole: None,
+            added: 0,
+            cleared: 0,
+        });
+        let grading = ruleset.mode.grading.then(Grading::new);
+        Self {
+            ruleset,
+            seed,
+            rng,
+            randomizer,
+            pieces,
+            scorer,
+            board,
+            active: None,
+            // The first piece spawns on the first step
+            phase: Phase::Entry { remaining: Duration::ZERO },
+            clearing_rows: Vec::new(),
+            next_queue,
+            hold: None,
+            can_hold: true,
+            score: Score {
+                level: start_level,
+                ..Score::default()
+            },
+            gravity_progress: 0.0,
+            dropped: 0,
+            combo_lines: 0,
+            lock_state: LockState::default(),
+            last_kick: None,
+            held: HeldButtons::default(),
+            armed: HeldButtons::default(),
+            dig,
+            grading,
+            elapsed: Duration::ZERO,
+            clock_running,
+            splits: Vec::new(),
+            ending: None,
+        }
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn active(&self) -> Option<&ActivePiece> {
+        self.active.as_ref()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Full rows on show during the line-clear delay, bottom first.
+    pub fn clearing_rows(&self) -> &[i32] {
+        &self.clearing_rows
+    }
+
+    /// Upcoming pieces, soonest first.
+    pub fn next_queue(&self) -> &VecDeque<PieceType> {
+        &self.next_queue
+    }
+
+    pub fn hold(&self) -> Option<PieceType> {
+        self.hold
+    }
+
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// True if the active piece's last successful move was a rotation (needed for spins).
+    pub fn last_move_was_rotation(&self) -> bool {
+        self.last_kick.is_some()
+    }
+
+    /// Kick index used by that rotation (0 = no offset).
+    pub fn last_kick(&self) -> Option<usize> {
+        self.last_kick.map(|kick| kick.index)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.ending.is_some()
+    }
+
+    /// How the game ended, once it has.
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Garbage rows cleared so far in Dig mode.
+    pub fn garbage_cleared(&self) -> Option<u32> {
+        self.dig.as_ref().map(|dig| dig.cleared)
+    }
+
+    /// The grade so far, in modes that award one.
+    pub fn grade(&self) -> Option<Grade> {
+        self.grading.map(|grading| grading.grade())
+    }
+
+    /// ARE, line-clear delay and lock delay at the current level.
+    pub fn delays(&self) -> Delays {
+        self.ruleset.speed.delays(self.score.level).unwrap_or(Delays {
+            are: self.ruleset.are,
+            line_clear_delay: self.ruleset.line_clear_delay,
+            lock_delay: self.ruleset.lock_delay,
+        })
+    }
+
+    /// Play time when each split was reached, in order.
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    /// Where the active piece would land if dropped straight down.
+    pub fn ghost(&self) -> Option<ActivePiece> {
+        let mut ghost = *self.active.as_ref()?;
+        while self.board.fits(&ghost.moved(0, -ghost.scale).cells()) {
+            ghost = ghost.moved(0, -ghost.scale);
+        }
+        Some(ghost)
+    }
+
+    /// Advances the game by `dt`, applying `inputs` first.
+    pub fn step(&mut self, inputs: Inputs, dt: Duration) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.is_over() {
+            return events;
+        }
+
+        // The frame of the first input starts the clock from zero
+        if self.clock_running {
+            self.elapsed += dt;
+        } else if inputs != Inputs::default() {
+            self.clock_running = true;
+        }
+        if let Some(limit) = self.ruleset.mode.time_limit
+            && self.elapsed >= limit
+        {
+            self.elapsed = limit;
+            self.end(Ending::TimeUp, &mut events);
+            return events;
+        }
+        self.held = inputs.held;
+        if self.active.is_none() {
+            self.armed = self.armed.or(HeldButtons::pressed(&inputs));
+        }
+        self.armed = self.armed.and(inputs.held);
+        // A piece spawned this frame has already seen these buttons through IRS/IHS
+        let spawned = self.run_delays(dt, &mut events);
+        if self.active.is_none() {
+            return events;
+        }
+        let hold = inputs.hold && !(spawned && self.ruleset.ihs);
+        let rotate = !(spawned && self.ruleset.irs);
+
+        if hold && self.can_hold {
+            self.hold_piece(&mut events);
+        } else {
+            if self.shift(inputs.shift) {
+                events.push(GameEvent::Moved);
+                self.on_manipulated();
+            }
+            let dropped = self.soft_drop(inputs.soft_drop);
+            if dropped > 0 {
+                self.dropped += dropped;
+                self.score.score += self.scorer.soft_drop(dropped);
+                events.push(GameEvent::Moved);
+                self.on_descended();
+            }
+            let turns = quarter_turns(inputs.rotate_cw, inputs.rotate_ccw, inputs.rotate_180);
+            if rotate && turns != 0 && let Some(kick) = self.try_rotate(turns) {
+                self.last_kick = Some(kick);
+                events.push(GameEvent::Rotated);
+                self.on_manipulated();
+            }
+            if inputs.hard_drop {
+                self.hard_drop(&mut events);
+                return events;
+            }
+            if inputs.sonic_drop {
+                let distance = self.drop_to_ghost();
+                if distance > 0 {
+                    self.dropped += distance;
+                    self.score.score += self.scorer.soft_drop(distance);
+                    events.push(GameEvent::SonicDropped(distance));
+                    self.on_descended();
+                }
+            }
+        }
+
+        self.apply_gravity(dt);
+        self.update_lock_delay(dt, &mut events);
+        events
+    }
+
+    /// Runs down the line-clear and entry delays, spawning once they are over.
+    /// True if a piece spawned.
+    fn run_delays(&mut self, mut dt: Duration, events: &mut Vec<GameEvent>) -> bool {
+        loop {
+            match self.phase {
+                Phase::Falling => return false,
+                Phase::LineClear { remaining } => {
+                    if dt < remaining {
+                        self.phase = Phase::LineClear { remaining: remaining - dt };
+                        return false;
+                    }
+                    dt -= remaining;
+                    self.board.clear_full_rows();
+                    self.clearing_rows.clear();
+                    events.push(GameEvent::LinesCollapsed);
+                    self.phase = Phase::Entry { remaining: self.delays().are };
+                }
+                Phase::Entry { remaining } => {
+                    if dt < remaining {
+                        self.phase = Phase::Entry { remaining: remaining - dt };
+                        return false;
+                    }
+                    self.phase = Phase::Falling;
+                    self.refill_garbage(events);
+                    if self.is_over() {
+                        return false;
+                    }
+                    self.spawn(events);
+                    return true;
+                }
+            }
+        }
+    }
+
+    fn take_next(&mut self) -> PieceType {
+        let piece_type = self.next_queue.pop_front().unwrap();
+        self.next_queue.push_back(self.randomizer.next_piece(&mut self.rng, &self.pieces));
+        piece_type
+    }
+
+    fn spawn(&mut self, events: &mut Vec<GameEvent>) {
+        let mut piece_type = self.take_next();
+        // IHS: straight into hold before it ever appears
+        if self.ruleset.ihs && self.initial_buttons().hold && self.can_hold {
+            piece_type = self.swap_hold(piece_type, events);
+        }
+        self.spawn_piece(piece_type, events);
+        // Used up; the next piece needs fresh presses
+        self.armed = HeldButtons::default();
+    }
+
+    /// The buttons IRS and IHS act on.
+    fn initial_buttons(&self) -> HeldButtons {
+        if self.ruleset.fresh_initial { self.armed } else { self.held }
+    }
+
+    fn spawn_piece(&mut self, piece_type: PieceType, events: &mut Vec<GameEvent>) {
+        let mut piece = ActivePiece::spawn(piece_type, &self.board, self.ruleset.rotation, self.ruleset.mode.piece_scale());
+        // IRS: appear already turned, if that fits where it spawns
+        let initial = self.initial_buttons();
+        let turns = quarter_turns(initial.rotate_cw, initial.rotate_ccw, initial.rotate_180);
+        if self.ruleset.irs && turns != 0 {
+            let turned = ActivePiece {
+                rotation_state: turns,
+                ..piece
+            };
+            if self.board.fits(&turned.cells()) {
+                piece = turned;
+            }
+        }
+        // Without block out, a blocked piece may rise through the buffer instead
+        if !self.ruleset.top_out.block_out {
+            while !self.board.fits(&piece.cells()) && piece.y < self.board.height() {
+                piece = piece.moved(0, piece.scale);
+            }
+        }
+        if !self.board.fits(&piece.cells()) {
+            self.end(Ending::TopOut(TopOut::BlockOut), events);
+            return;
+        }
+        self.active = Some(piece);
+        self.lock_state = LockState {
+            lowest_y: piece.y,
+            ..LockState::default()
+        };
+        self.last_kick = None;
+        self.gravity_progress = 0.0;
+        self.dropped = 0;
+        events.push(GameEvent::Spawned(piece_type));
+    }
+
+    fn hold_piece(&mut self, events: &mut Vec<GameEvent>) {
+        let Some(current) = self.active.take() else { return };
+        let piece_type = self.swap_hold(current.piece_type, events);
+        self.spawn_piece(piece_type, events);
+    }
+
+    /// Puts `piece_type` in hold and returns the piece to play instead.
+    fn swap_hold(&mut self, piece_type: PieceType, events: &mut Vec<GameEvent>) -> PieceType {
+        self.can_hold = false;
+        events.push(GameEvent::Held(piece_type));
+
+        // Swap, or take from next on the first hold
+        match self.hold.replace(piece_type) {
+            Some(held_type) => held_type,
+            None => self.take_next(),
+        }
+    }
+
+    /// Moves by (dx, dy) blocks.
+    fn try_move(&mut self, dx: i32, dy: i32) -> bool {
+        let Some(piece) = self.active else { return false };
+        let moved = piece.moved(dx * piece.scale, dy * piece.scale);
+        if !self.board.fits(&moved.cells()) {
+            return false;
+        }
+        self.active = Some(moved);
+        self.last_kick = None;
+        true
+    }
+
+    /// Moves up to `cells` sideways, stopping at the first obstacle. True if it moved at all.
+    fn shift(&mut self, cells: i32) -> bool {
+        let dx = cells.signum();
+        let mut moved = false;
+        for _ in 0..cells.abs() {
+            if !self.try_move(dx, 0) {
+                break;
+            }
+            moved = true;
+        }
+        moved
+    }
+
+    /// Moves down up to `cells`, returning how far it got.
+    fn soft_drop(&mut self, cells: u32) -> u32 {
+        let mut moved = 0;
+        for _ in 0..cells {
+            if !self.try_move(0, -1) {
+                break;
+            }
+            moved += 1;
+        }
+        moved
+    }
+
+    /// Rotates by `turns` quarter turns clockwise, returning the index of the kick that fit.
+    fn try_rotate(&mut self, turns: usize) -> Option<Kick> {
+        let piece = self.active?;
+        let new_rot = (piece.rotation_state + turns) % 4;
+        let kicks = piece.rotation_system.system().kicks(&self.board, &piece, turns, self.ruleset.kicks_180);
+
+        // Take the first kick that fits
+        for (index, (kx, ky)) in kicks.into_iter().enumerate() {
+            let candidate = ActivePiece {
+                rotation_state: new_rot,
+                ..piece.moved(kx * piece.scale, ky * piece.scale)
+            };
+            if self.board.fits(&candidate.cells()) {
+                self.active = Some(candidate);
+                return Some(Kick::new(&piece, turns, index));
+            }
+        }
+        None
+    }
+
+    /// Moves the active piece straight down as far as it goes, returning the distance in blocks.
+    fn drop_to_ghost(&mut self) -> u32 {
+        let (Some(piece), Some(ghost)) = (self.active, self.ghost()) else { return 0 };
+        if ghost.y != piece.y {
+            self.active = Some(ghost);
+            self.last_kick = None;
+        }
+        ((piece.y - ghost.y) / piece.scale) as u32
+    }
+
+    fn hard_drop(&mut self, events: &mut Vec<GameEvent>) {
+        if self.active.is_none() {
+            return;
+        }
+        let distance = self.drop_to_ghost();
+        self.dropped += distance;
+        self.score.score += self.scorer.hard_drop(distance);
+        events.push(GameEvent::HardDropped(distance));
+        self.lock(events);
+    }
+
+    /// Drops as many whole cells as gravity has built up. Landing is handled by the lock delay, not here.
+    fn apply_gravity(&mut self, dt: Duration) {
+        let g = self.ruleset.speed.gravity(self.score.level);
+        if g >= MAX_GRAVITY {
+            // 20G: straight onto the stack, even on the frame it spawns
+            if self.drop_to_ghost() > 0 {
+                self.on_descended();
+            }
+            return;
+        }
+
+        self.gravity_progress += g * gravity::frames(dt);
+        let mut moved = false;
+        while self.gravity_progress >= 1.0 {
+            if !self.try_move(0, -1) {
+                // Resting pieces don't bank gravity for later
+                self.gravity_progress = 0.0;
+                break;
+            }
+            self.gravity_progress -= 1.0;
+            moved = true;
+        }
+        if moved {
+            self.on_descended();
+        }
+    }
+
+    fn is_grounded(&self) -> bool {
+        self.active
+            .is_some_and(|piece| !self.board.fits(&piece.moved(0, -piece.scale).cells()))
+    }
+
+    /// A new lowest row restarts the lock delay (and the move-reset allowance).
+    fn on_descended(&mut self) {
+        let Some(piece) = self.active else { return };
+        if piece.y >= self.lock_state.lowest_y {
+            return;
+        }
+        self.lock_state.lowest_y = piece.y;
+        if self.ruleset.lock_reset != LockReset::None {
+            self.lock_state.elapsed = Duration::ZERO;
+            self.lock_state.resets = 0;
+        }
+    }
+
+    /// A successful move or rotate: may restart the lock delay if the piece is resting.
+    fn on_manipulated(&mut self) {
+        self.on_descended();
+        if self.ruleset.lock_reset == LockReset::Move
+            && self.lock_state.grounded
+            && self.lock_state.resets < self.ruleset.max_lock_resets
+        {
+            self.lock_state.elapsed = Duration::ZERO;
+            self.lock_state.resets += 1;
+        }
+    }
+
+    fn update_lock_delay(&mut self, dt: Duration, events: &mut Vec<GameEvent>) {
+        self.lock_state.grounded = self.is_grounded();
+        if !self.lock_state.grounded {
+            return;
+        }
+        self.lock_state.elapsed += dt;
+
+        // Out of move resets: lock as soon as it touches down
+        let resets_exhausted = self.ruleset.lock_reset == LockReset::Move
+            && self.lock_state.resets >= self.ruleset.max_lock_resets;
+        if resets_exhausted || self.lock_state.elapsed >= self.delays().lock_delay {
+            self.lock(events);
+        }
+    }
+
+    fn lock(&mut self, events: &mut Vec<GameEvent>) {
+        let Some(piece) = self.active.take() else { return };
+        // Spins are judged against the stack before the piece joins it
+        let t_spin = spin::classify(&self.board, &piece, self.last_kick);
+        for (x, y) in piece.cells() {
+            self.board.set_at(x, y, Cell::Piece(piece.piece_type), self.elapsed);
+        }
+        self.can_hold = true;
+        self.last_kick = None;
+        self.score.pieces += 1;
+        events.push(GameEvent::Locked { t_spin });
+
+        let cleared_rows = self.board.full_rows();
+        // Big blocks clear rows in pairs, which count as one line
+        let cleared = (cleared_rows.len() as u32).div_ceil(piece.scale as u32);
+        let perfect_clear = cleared > 0 && self.board.is_empty_except(&cleared_rows);
+        if let Some(dig) = &mut self.dig {
+            dig.cleared += cleared_rows.iter().filter(|&&y| self.board.is_garbage_row(y)).count() as u32;
+        }
+        if cleared > 0 {
+            let lines_before = self.score.lines_cleared;
+            self.score.lines_cleared += cleared;
+            if let Some(split) = self.ruleset.mode.split_lines.filter(|&split| split > 0) {
+                let crossed = self.score.lines_cleared / split - lines_before / split;
+                self.splits.extend((0..crossed).map(|_| self.elapsed));
+            }
+            events.push(GameEvent::LinesCleared(cleared));
+        }
+        self.score_lock(cleared, t_spin, perfect_clear);
+        let levels_done = self.advance_level(cleared, events);
+
+        let dug_out = self.dig.as_ref().is_some_and(|dig| dig.cleared >= dig.config.total);
+        let goal_met = self.ruleset.mode.line_goal.is_some_and(|goal| self.score.lines_cleared >= goal);
+        if dug_out || goal_met || levels_done {
+            self.end(Ending::Cleared, events);
+            return;
+        }
+        if let Some(reason) = self.lock_out(&piece, &cleared_rows) {
+            self.end(Ending::TopOut(reason), events);
+            return;
+        }
+        let delays = self.delays();
+        self.phase = if cleared > 0 {
+            Phase::LineClear { remaining: delays.line_clear_delay }
+        } else {
+            Phase::Entry { remaining: delays.are }
+        };
+        self.clearing_rows = cleared_rows;
+        // Zero delays play out straight away
+        self.run_delays(Duration::ZERO, events);
+    }
+
+    /// Levels up after a lock that cleared `cleared` lines. True once a sectioned mode reaches its last level.
+    fn advance_level(&mut self, cleared: u32, events: &mut Vec<GameEvent>) -> bool {
+        let from = self.score.level;
+        let to = self.next_level(cleared);
+        match self.ruleset.mode.leveling {
+            Leveling::Lines => {
+                if to > from {
+                    self.score.level = to;
+                    events.push(GameEvent::LevelUp(to));
+                }
+                false
+            }
+            Leveling::Sections { max_level } => {
+                self.score.level = to;
+                let sections = to / Leveling::SECTION - from / Leveling::SECTION;
+                self.splits.extend((0..sections).map(|_| self.elapsed));
+                if sections > 0 {
+                    events.push(GameEvent::LevelUp(to));
+                }
+                if let Some(grading) = &mut self.grading {
+                    grading.update(self.score.score, from, to, self.elapsed);
+                }
+                to >= max_level
+            }
+        }
+    }
+
+    /// The level a lock that cleared `cleared` lines leads to. `score.lines_cleared` already counts them.
+    fn next_level(&self, cleared: u32) -> u32 {
+        let from = self.score.level;
+        match self.ruleset.mode.leveling {
+            // Level up every 10 lines
+            Leveling::Lines => from.max(self.ruleset.mode.first_level() + self.score.lines_cleared / 10),
+            // Every piece counts one level except at a stop (x99, and the last level but one); lines always count
+            Leveling::Sections { max_level } => {
+                let at_stop = from % Leveling::SECTION == Leveling::SECTION - 1 || from + 1 >= max_level;
+                (from + u32::from(!at_stop) + cleared).min(max_level)
+            }
+        }
+    }
+
+    /// Checks where the piece's surviving blocks ended up once the cleared rows below them were removed.
+    fn lock_out(&self, piece: &ActivePiece, cleared_rows: &[i32]) -> Option<TopOut> {
+        let rows: Vec<i32> = piece
+            .cells()
+            .into_iter()
+            .filter(|(_, y)| !cleared_rows.contains(y))
+            .map(|(_, y)| y - cleared_rows.iter().filter(|&&row| row < y).count() as i32)
+            .collect();
+        let hidden = rows.iter().filter(|&&y| y >= self.board.visible_height()).count();
+        let rules = self.ruleset.top_out;
+        if rules.lock_out && hidden > 0 && hidden == rows.len() {
+            Some(TopOut::LockOut)
+        } else if rules.partial_lock_out && hidden > 0 {
+            Some(TopOut::PartialLockOut)
+        } else {
+            None
+        }
+    }
+
+    /// Raises the stack by one garbage row per entry in `holes`, each open at that column.
+    /// The active piece is pushed up if the garbage runs into it.
+    pub fn add_garbage(&mut self, holes: &[i32]) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if !self.is_over() {
+            self.raise_garbage(holes, &mut events);
+        }
+        events
+    }
+
+    /// Dig mode: tops the garbage back up to its height, while there is more to come.
+    fn refill_garbage(&mut self, events: &mut Vec<GameEvent>) {
+        let Some(dig) = &mut self.dig else { return };
+        let on_board = (0..self.board.height()).filter(|&y| self.board.is_garbage_row(y)).count() as u32;
+        let rows = dig
+            .config
+            .height
+            .saturating_sub(on_board)
+            .min(dig.config.total.saturating_sub(dig.added));
+        let width = self.board.width();
+        let holes: Vec<i32> = (0..rows).map(|_| dig.next_hole(width)).collect();
+        self.raise_garbage(&holes, events);
+    }
+
+    fn raise_garbage(&mut self, holes: &[i32], events: &mut Vec<GameEvent>) {
+        if holes.is_empty() {
+            return;
+        }
+        let mut overflowed = false;
+        for &hole in holes {
+            overflowed |= self.board.push_garbage(hole, self.elapsed);
+        }
+        for row in &mut self.clearing_rows {
+            *row += holes.len() as i32;
+        }
+        if let Some(mut piece) = self.active {
+            while !self.board.fits(&piece.cells()) && piece.y < self.board.height() {
+                piece = piece.moved(0, piece.scale);
+            }
+            self.active = Some(piece);
+            self.lock_state.lowest_y = self.lock_state.lowest_y.max(piece.y);
+        }
+        events.push(GameEvent::GarbageRaised(holes.len() as u32));
+        if overflowed && self.ruleset.top_out.garbage_out {
+            self.end(Ending::TopOut(TopOut::GarbageOut), events);
+        }
+    }
+
+    fn end(&mut self, ending: Ending, events: &mut Vec<GameEvent>) {
+        self.active = None;
+        self.ending = Some(ending);
+        events.push(GameEvent::GameOver(ending));
+    }
+
+    /// Updates the combo and back-to-back streaks and awards the clear, at the level it happened on.
+    fn score_lock(&mut self, lines: u32, t_spin: TSpin, perfect_clear: bool) {
+        self.score.combo = if lines > 0 { self.score.combo + 1 } else { 0 };
+        self.combo_lines = if lines > 0 { self.combo_lines + lines } else { 0 };
+        if lines == 0 && t_spin == TSpin::None {
+            return;
+        }
+        let mut clear = LineClear {
+            lines,
+            t_spin,
+            perfect_clear,
+            combo: self.score.combo,
+            combo_lines: self.combo_lines,
+            back_to_back: false,
+            dropped: self.dropped,
+            level_after: self.next_level(lines),
+        };
+        // Spins without lines neither start nor break a streak
+        if lines > 0 {
+            let difficult = clear.is_difficult();
+            clear.back_to_back = difficult && self.score.back_to_back;
+            self.score.back_to_back = difficult;
+        }
+        self.score.score += self.scorer.line_clear(&clear, self.score.level);
+    }
+}
+
+/// Quarter turns clockwise for the rotate buttons: 1 = CW, 2 = 180, 3 = CCW, 0 = none.
+fn quarter_turns(rotate_cw: bool, rotate_ccw: bool, rotate_180: bool) -> usize {
+    if rotate_180 {
+        2
+    } else if rotate_cw {
+        1
+    } else if rotate_ccw {
+        3
+    } else {
+        0
+    }
+}
+
+/// A fresh seed for when the player didn't ask for one.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::engine::board::WIDTH_RANGE;
+    use crate::engine::mode::{Mode, ModeKind};
+    use crate::engine::piece::PieceSet;
+    use crate::engine::speed::SpeedCurve;
+    use crate::engine::ruleset::TopOutRules;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn piece(name: &str) -> PieceType {
+        PieceSet::tetromino().find(name).unwrap()
+    }
+
+    /// Fills rows `0..rows` except for `hole`.
+    fn fill_rows(game: &mut Game, rows: i32, hole: i32) {
+        for y in 0..rows {
+            for x in (0..game.board.width()).filter(|&x| x != hole) {
+                game.board.set(x, y, Cell::Garbage);
+            }
+        }
+    }
+
+    /// Swaps the active piece for a vertical I above the left wall.
+    fn vertical_i_at_left_wall(game: &mut Game) {
+        let mut i = ActivePiece::spawn(piece("I"), &game.board, RotationKind::Srs, 1);
+        i.rotation_state = 1;
+        i.x = -i.blocks().iter().map(|&(x, _)| x - i.x).min().unwrap();
+        i.y = game.board.visible_height() - 4;
+        game.active = Some(i);
+    }
+
+    #[test]
+    fn every_piece_spawns_inside_the_narrowest_board() {
+        for big in [false, true] {
+            let ruleset = Ruleset { width: *WIDTH_RANGE.start(), mode: Mode { big, ..Mode::default() }, ..Ruleset::default() };
+            let scale = ruleset.mode.piece_scale();
+            let board = ruleset.board();
+            for name in PieceSet::BUNDLED {
+                for piece_type in PieceSet::bundled(name).unwrap().types() {
+                    for rotation in RotationKind::ALL {
+                        // The pentomino I is wider than the board however it's placed
+                        let columns: HashSet<i32> = rotation.system().offsets(piece_type, 0).iter().map(|&(x, _)| x).collect();
+                        if columns.len() as i32 * scale > board.width() {
+                            continue;
+                        }
+                        let piece = ActivePiece::spawn(piece_type, &board, rotation, scale);
+                        assert!(
+                            board.fits(&piece.cells()) && piece.x % scale == 0 && piece.y % scale == 0,
+                            "{} from {} spawns outside a {}-wide board under {} (scale {})",
+                            piece_type.name(),
+                            name,
+                            board.width(),
+                            rotation.name(),
+                            scale
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn big_mode_rounds_the_board_to_whole_blocks() {
+        for (width, height, buffer_rows) in [(9, 21, 20), (5, 20, 3), (4, 11, 0)] {
+            let mode = Mode { big: true, ..Mode::default() };
+            let ruleset = Ruleset { width, height, buffer_rows, mode, ..Ruleset::default() };
+            let board = ruleset.board();
+            assert!(board.width() >= 8 && board.width() % 2 == 0 && board.height() % 2 == 0);
+            let mut game = Game::new(ruleset, 0);
+            game.step(Inputs::default(), Duration::ZERO);
+            let ghost = game.ghost().unwrap();
+            assert_eq!(ghost.cells().iter().map(|&(_, y)| y).min(), Some(0), "{}x{}+{}", width, height, buffer_rows);
+        }
+    }
+
+    #[test]
+    fn first_bag_plays_out_on_the_narrowest_board() {
+        for seed in 0..5 {
+            let ruleset = Ruleset { width: *WIDTH_RANGE.start(), ..Ruleset::default() };
+            let mut game = Game::new(ruleset, seed);
+            let drop = Inputs { hard_drop: true, ..Inputs::default() };
+            for _ in 0..7 {
+                game.step(drop, Duration::ZERO);
+            }
+            assert!(!game.is_over(), "seed {} topped out", seed);
+        }
+    }
+
+    #[test]
+    fn buttons_held_at_spawn_act_on_it() {
+        let mut game = Game::new(Ruleset::default(), 1);
+        let held = HeldButtons { rotate_cw: true, ..HeldButtons::default() };
+        game.step(Inputs::default(), Duration::ZERO);
+        // Rotate goes down with the hard drop and stays down while the next piece spawns
+        game.step(Inputs { hard_drop: true, rotate_cw: true, held, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.active().unwrap().rotation_state, 1);
+    }
+
+    #[test]
+    fn fresh_initial_ignores_buttons_kept_down_from_the_last_piece() {
+        let mut game = Game::new(Ruleset { fresh_initial: true, ..Ruleset::default() }, 1);
+        let held = HeldButtons { rotate_cw: true, hold: true, ..HeldButtons::default() };
+        game.step(Inputs::default(), Duration::ZERO);
+        // Rotate and hold go down with the hard drop and stay down
+        game.step(Inputs { hard_drop: true, held, ..Inputs::default() }, Duration::ZERO);
+        game.step(Inputs { held, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.active().unwrap().rotation_state, 0);
+        assert_eq!(game.hold(), None);
+    }
+
+    #[test]
+    fn fresh_initial_takes_buttons_pressed_between_pieces() {
+        let ruleset = Ruleset { are: Duration::from_millis(100), fresh_initial: true, ..Ruleset::default() };
+        let mut game = Game::new(ruleset, 1);
+        game.step(Inputs::default(), Duration::ZERO);
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        let held = HeldButtons { rotate_cw: true, ..HeldButtons::default() };
+        game.step(Inputs { rotate_cw: true, held, ..Inputs::default() }, Duration::from_millis(50));
+        game.step(Inputs { held, ..Inputs::default() }, Duration::from_millis(50));
+        assert_eq!(game.active().unwrap().rotation_state, 1);
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let script = |i: i32| Inputs {
+            shift: i % 5 - 2,
+            rotate_cw: i % 3 == 0,
+            hard_drop: i % 11 == 0,
+            hold: i % 29 == 0,
+            ..Inputs::default()
+        };
+        let mut a = Game::new(Ruleset::default(), 7);
+        let mut b = Game::new(Ruleset::default(), 7);
+        for i in 0..2000 {
+            assert_eq!(a.step(script(i), FRAME), b.step(script(i), FRAME));
+            assert_eq!(a.next_queue(), b.next_queue());
+            assert_eq!(a.score(), b.score());
+        }
+    }
+
+    #[test]
+    fn seeded_sequence_is_fixed() {
+        // Pinned so a change of generator or shuffle shows up as a failure, not a silent reshuffle
+        let mut game = Game::new(Ruleset { previews: 1, ..Ruleset::default() }, 0);
+        let drop = Inputs { hard_drop: true, ..Inputs::default() };
+        let mut sequence = String::new();
+        while sequence.len() < 14 {
+            for event in game.step(drop, Duration::ZERO) {
+                if let GameEvent::Spawned(piece_type) = event {
+                    sequence.push_str(piece_type.name());
+                }
+            }
+            // Keep the stack low so the game doesn't end first
+            game.board = game.ruleset.board();
+        }
+        assert_eq!(sequence, "STOJZILOLTZJIS");
+    }
+
+    #[test]
+    fn move_resets_run_out_after_fifteen() {
+        let mut game = Game::new(Ruleset::default(), 5);
+        game.step(Inputs { sonic_drop: true, ..Inputs::default() }, Duration::ZERO);
+        // Each move comes well inside the 500ms lock delay, but they add up to far more than it
+        for i in 1..=15 {
+            let shift = if i % 2 == 0 { 1 } else { -1 };
+            let events = game.step(Inputs { shift, ..Inputs::default() }, Duration::from_millis(400));
+            let locked = events.iter().any(|event| matches!(event, GameEvent::Locked { .. }));
+            assert_eq!(locked, i == 15, "move {}", i);
+        }
+    }
+
+    #[test]
+    fn lock_delay_runs_out_without_moves() {
+        let mut game = Game::new(Ruleset::default(), 5);
+        game.step(Inputs { sonic_drop: true, ..Inputs::default() }, Duration::ZERO);
+        let events = game.step(Inputs::default(), Duration::from_millis(499));
+        assert!(!events.iter().any(|event| matches!(event, GameEvent::Locked { .. })));
+        let events = game.step(Inputs::default(), Duration::from_millis(1));
+        assert!(events.iter().any(|event| matches!(event, GameEvent::Locked { .. })));
+    }
+
+    #[test]
+    fn back_to_back_tetrises_ending_in_a_perfect_clear() {
+        let mut game = Game::new(Ruleset::default(), 3);
+        game.step(Inputs::default(), Duration::ZERO);
+        fill_rows(&mut game, 8, 0);
+        let drop = Inputs { hard_drop: true, ..Inputs::default() };
+        let mut points = Vec::new();
+        for _ in 0..2 {
+            vertical_i_at_left_wall(&mut game);
+            let before = game.score().score;
+            let events = game.step(drop, Duration::ZERO);
+            assert!(events.contains(&GameEvent::LinesCleared(4)));
+            let dropped = events.iter().find_map(|event| match event {
+                GameEvent::HardDropped(cells) => Some(*cells),
+                _ => None,
+            });
+            points.push(game.score().score - before - 2 * dropped.unwrap());
+        }
+        // A tetris, then a back-to-back tetris with a combo of 2 that empties the board
+        assert_eq!(points, [800, 1200 + 50 + 3200]);
+        assert!(game.score().back_to_back);
+        assert_eq!(game.score().combo, 2);
+    }
+
+    /// A game whose stack reaches the top of the visible rows, with no full rows to clear.
+    fn stacked_to_the_top(top_out: TopOutRules) -> Game {
+        let mut game = Game::new(Ruleset { top_out, ..Ruleset::default() }, 9);
+        let (width, height) = (game.board.width(), game.board.visible_height());
+        fill_rows(&mut game, height, width - 1);
+        game
+    }
+
+    #[test]
+    fn locking_above_the_visible_rows_is_lock_out() {
+        let mut game = stacked_to_the_top(TopOutRules::default());
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.ending(), Some(Ending::TopOut(TopOut::LockOut)));
+    }
+
+    #[test]
+    fn without_lock_out_the_next_spawn_blocks_out() {
+        let top_out = TopOutRules { lock_out: false, ..TopOutRules::default() };
+        let mut game = stacked_to_the_top(top_out);
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.ending(), Some(Ending::TopOut(TopOut::BlockOut)));
+    }
+
+    #[test]
+    fn blocked_spawn_is_block_out() {
+        let mut game = Game::new(Ruleset::default(), 9);
+        let spawn_row = game.board.spawn_row(1);
+        for x in 0..game.board.width() {
+            for y in spawn_row - 1..=spawn_row + 1 {
+                game.board.set(x, y, Cell::Garbage);
+            }
+        }
+        game.step(Inputs::default(), Duration::ZERO);
+        assert_eq!(game.ending(), Some(Ending::TopOut(TopOut::BlockOut)));
+    }
+
+    fn delayed() -> Ruleset {
+        Ruleset {
+            are: Duration::from_millis(100),
+            line_clear_delay: Duration::from_millis(200),
+            ..Ruleset::default()
+        }
+    }
+
+    #[test]
+    fn entry_delay_withholds_the_next_piece() {
+        let mut game = Game::new(delayed(), 2);
+        game.step(Inputs::default(), Duration::ZERO);
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.phase(), Phase::Entry { remaining: Duration::from_millis(100) });
+        assert!(game.active().is_none());
+        game.step(Inputs::default(), Duration::from_millis(99));
+        assert!(game.active().is_none());
+        let events = game.step(Inputs::default(), Duration::from_millis(1));
+        assert!(matches!(events[..], [GameEvent::Spawned(_), ..]));
+        assert_eq!(game.phase(), Phase::Falling);
+    }
+
+    #[test]
+    fn cleared_rows_stay_through_the_line_clear_delay_then_entry_follows() {
+        let mut game = Game::new(delayed(), 2);
+        game.step(Inputs::default(), Duration::ZERO);
+        fill_rows(&mut game, 4, 0);
+        vertical_i_at_left_wall(&mut game);
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.phase(), Phase::LineClear { remaining: Duration::from_millis(200) });
+        assert_eq!(game.clearing_rows(), [0, 1, 2, 3]);
+        assert_eq!(game.board().full_rows().len(), 4);
+
+        // Time left over from the line-clear delay counts towards entry
+        let events = game.step(Inputs::default(), Duration::from_millis(250));
+        assert_eq!(events, [GameEvent::LinesCollapsed]);
+        assert!(game.board().full_rows().is_empty());
+        assert_eq!(game.phase(), Phase::Entry { remaining: Duration::from_millis(50) });
+        game.step(Inputs::default(), Duration::from_millis(50));
+        assert!(game.active().is_some());
+    }
+
+    fn dig(config: DigGarbage) -> Game {
+        let mut mode = Mode::new(ModeKind::Dig);
+        mode.dig = Some(config);
+        Game::new(Ruleset { mode, ..Ruleset::default() }, 4)
+    }
+
+    fn holes(game: &Game, y: i32) -> Vec<i32> {
+        (0..game.board.width()).filter(|&x| game.board.get(x, y).is_none()).collect()
+    }
+
+    fn garbage_rows(game: &Game) -> usize {
+        (0..game.board.height()).filter(|&y| game.board.is_garbage_row(y)).count()
+    }
+
+    #[test]
+    fn dig_starts_at_its_garbage_height_with_one_hole_per_row() {
+        let mut game = dig(DigGarbage { hole_change: 0.0, ..DigGarbage::default() });
+        let events = game.step(Inputs::default(), Duration::ZERO);
+        assert!(events.contains(&GameEvent::GarbageRaised(10)));
+        assert_eq!(garbage_rows(&game), 10);
+        // No hole changes: one well all the way down
+        let well = holes(&game, 0);
+        assert_eq!(well.len(), 1);
+        assert!((0..10).all(|y| holes(&game, y) == well));
+        assert_eq!(game.garbage_cleared(), Some(0));
+    }
+
+    #[test]
+    fn cleared_garbage_is_counted_and_refilled_up_to_the_total() {
+        let mut game = dig(DigGarbage { total: 12, ..DigGarbage::default() });
+        game.step(Inputs::default(), Duration::ZERO);
+        // Plug the top three rows so the next lock clears them
+        for y in 7..10 {
+            for x in holes(&game, y) {
+                game.board.set(x, y, Cell::Garbage);
+            }
+        }
+        let events = game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.garbage_cleared(), Some(3));
+        // Back towards 10 rows, but only 2 of the 12 are left to come
+        assert!(events.contains(&GameEvent::GarbageRaised(2)));
+        assert_eq!(garbage_rows(&game), 9);
+    }
+
+    #[test]
+    fn clearing_every_garbage_row_finishes_the_dig() {
+        let mut game = dig(DigGarbage { total: 3, ..DigGarbage::default() });
+        game.step(Inputs::default(), Duration::ZERO);
+        assert_eq!(garbage_rows(&game), 3);
+        for y in 0..3 {
+            for x in holes(&game, y) {
+                game.board.set(x, y, Cell::Garbage);
+            }
+        }
+        game.step(Inputs { hard_drop: true, ..Inputs::default() }, Duration::ZERO);
+        assert_eq!(game.garbage_cleared(), Some(3));
+        assert_eq!(game.ending(), Some(Ending::Cleared));
+    }
+
+    /// Master's levels and grading without its delays, so each step plays a whole piece.
+    fn master_at(level: u32) -> Game {
+        let ruleset = Ruleset::for_mode(Mode::new(ModeKind::Master));
+        let ruleset = Ruleset {
+            speed: SpeedCurve { delays: Vec::new(), ..ruleset.speed },
+            are: Duration::ZERO,
+            line_clear_delay: Duration::ZERO,
+            ..ruleset
+        };
+        let mut game = Game::new(ruleset, 6);
+        game.score.level = level;
+        game.step(Inputs::default(), Duration::ZERO);
+        game
+    }
+
+    #[test]
+    fn sections_count_pieces_but_stop_at_x99_until_a_clear() {
+        let mut game = master_at(97);
+        let drop = Inputs { hard_drop: true, ..Inputs::default() };
+        game.step(drop, Duration::ZERO);
+        game.step(drop, Duration::ZERO);
+        assert_eq!(game.score().level, 99);
+        game.step(drop, Duration::ZERO);
+        assert_eq!(game.score().level, 99);
+        assert!(game.splits().is_empty());
+
+        fill_rows(&mut game, 1, 0);
+        vertical_i_at_left_wall(&mut game);
+        let events = game.step(drop, Duration::ZERO);
+        // Lines still count at a stop
+        assert_eq!(game.score().level, 100);
+        assert!(events.contains(&GameEvent::LevelUp(100)));
+        assert_eq!(game.splits().len(), 1);
+    }
+
+    #[test]
+    fn the_last_level_needs_a_clear_and_ends_the_game() {
+        let mut game = master_at(997);
+        let drop = Inputs { hard_drop: true, ..Inputs::default() };
+        game.step(drop, Duration::ZERO);
+        game.step(drop, Duration::ZERO);
+        assert_eq!(game.score().level, 998);
+        assert!(!game.is_over());
+
+        fill_rows(&mut game, 2, 0);
+        vertical_i_at_left_wall(&mut game);
+        game.step(drop, Duration::ZERO);
+        assert_eq!(game.score().level, Mode::MASTER_LEVELS);
+        assert_eq!(game.ending(), Some(Ending::Cleared));
+        assert!(game.grade().is_some());
+    }
+}
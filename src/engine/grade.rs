@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+/// TGM grades, from 9 up through 1 and S1–S9 to GM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Grade(u8);
+
+impl Grade {
+    const NAMES: [&'static str; 19] = [
+        "9", "8", "7", "6", "5", "4", "3", "2", "1", "S1", "S2", "S3", "S4", "S5", "S6", "S7", "S8", "S9", "GM",
+    ];
+    // Score needed for each grade below GM
+    const SCORES: [u32; 18] = [
+        0, 400, 800, 1400, 2000, 3500, 5500, 8000, 12000, 16000, 22000, 30000, 40000, 52000, 66000, 82000,
+        100000, 120000,
+    ];
+    const ONE: Grade = Grade(8);
+    const S4: Grade = Grade(12);
+    const S9: Grade = Grade(17);
+    pub const GM: Grade = Grade(18);
+
+    /// The best grade below GM that `score` earns.
+    pub fn from_score(score: u32) -> Self {
+        Grade(Self::SCORES.iter().rposition(|&needed| score >= needed).unwrap_or(0) as u8)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self.0 as usize]
+    }
+}
+
+/// Tracks the grade through a Master game. Score sets the grade; GM also needs the
+/// player to pass each checkpoint in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grading {
+    grade: Grade,
+    gm_eligible: bool,
+}
+
+impl Grading {
+    // (level, minimum grade, time limit) to stay in the running for GM
+    const CHECKPOINTS: [(u32, Grade, Duration); 3] = [
+        (300, Grade::ONE, Duration::from_secs(4 * 60 + 15)),
+        (500, Grade::S4, Duration::from_secs(7 * 60 + 30)),
+        (999, Grade::S9, Duration::from_secs(13 * 60 + 30)),
+    ];
+    pub const GM_LEVEL: u32 = 999;
+    pub const GM_SCORE: u32 = 126000;
+
+    pub fn new() -> Self {
+        Self {
+            grade: Grade::default(),
+            gm_eligible: true,
+        }
+    }
+
+    pub fn grade(&self) -> Grade {
+        self.grade
+    }
+
+    /// After a lock took the level from `from` to `to`.
+    pub fn update(&mut self, score: u32, from: u32, to: u32, elapsed: Duration) {
+        self.grade = self.grade.max(Grade::from_score(score));
+        for (level, grade, time) in Self::CHECKPOINTS {
+            if from < level && to >= level && (self.grade < grade || elapsed > time) {
+                self.gm_eligible = false;
+            }
+        }
+        if to >= Self::GM_LEVEL && self.gm_eligible && score >= Self::GM_SCORE {
+            self.grade = Grade::GM;
+        }
+    }
+}
+
+impl Default for Grading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(minutes: u64, seconds: u64) -> Duration {
+        Duration::from_secs(minutes * 60 + seconds)
+    }
+
+    #[test]
+    fn score_sets_the_grade_below_gm() {
+        assert_eq!(Grade::from_score(0).name(), "9");
+        assert_eq!(Grade::from_score(399).name(), "9");
+        assert_eq!(Grade::from_score(400).name(), "8");
+        assert_eq!(Grade::from_score(12000).name(), "1");
+        assert_eq!(Grade::from_score(120000).name(), "S9");
+        assert_eq!(Grade::from_score(u32::MAX).name(), "S9");
+    }
+
+    #[test]
+    fn gm_needs_every_checkpoint_in_time() {
+        let mut grading = Grading::new();
+        grading.update(12000, 298, 300, minutes(4, 0));
+        grading.update(40000, 499, 501, minutes(7, 0));
+        grading.update(125999, 990, 999, minutes(13, 0));
+        assert_eq!(grading.grade().name(), "S9");
+        grading.update(126000, 999, 999, minutes(13, 0));
+        assert_eq!(grading.grade(), Grade::GM);
+    }
+
+    #[test]
+    fn a_late_checkpoint_rules_out_gm() {
+        let mut grading = Grading::new();
+        grading.update(12000, 298, 300, minutes(4, 16));
+        grading.update(40000, 499, 501, minutes(7, 0));
+        grading.update(200000, 990, 999, minutes(13, 0));
+        assert_eq!(grading.grade().name(), "S9");
+    }
+
+    #[test]
+    fn a_low_grade_at_a_checkpoint_rules_out_gm() {
+        let mut grading = Grading::new();
+        grading.update(8000, 298, 300, minutes(4, 0));
+        grading.update(40000, 499, 501, minutes(7, 0));
+        grading.update(200000, 990, 999, minutes(13, 0));
+        assert_eq!(grading.grade().name(), "S9");
+    }
+
+    #[test]
+    fn grades_never_drop() {
+        let mut grading = Grading::new();
+        grading.update(5500, 0, 10, minutes(1, 0));
+        grading.update(0, 10, 20, minutes(1, 30));
+        assert_eq!(grading.grade().name(), "3");
+    }
+}
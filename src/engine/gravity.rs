@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+/// Gravity is measured in cells per frame at this rate, as in the guideline.
+pub const FRAME_RATE: f32 = 60.0;
+/// 20G: the piece falls the whole visible field in one frame, so it appears on the stack.
+pub const MAX_GRAVITY: f32 = 20.0;
+
+/// Guideline gravity at `level` in cells per frame: `(0.8 - (level - 1) * 0.007)^(level - 1)`
+/// seconds per row, capped at 20G.
+pub fn gravity(level: u32) -> f32 {
+    let n = level.max(1) as f32 - 1.0;
+    let seconds_per_row = (0.8 - n * 0.007).max(0.0).powf(n);
+    if seconds_per_row <= 0.0 {
+        return MAX_GRAVITY;
+    }
+    (1.0 / (seconds_per_row * FRAME_RATE)).min(MAX_GRAVITY)
+}
+
+/// `dt` in (fractional) frames.
+pub fn frames(dt: Duration) -> f32 {
+    dt.as_secs_f32() * FRAME_RATE
+}
@@ -0,0 +1,28 @@
+//! Headless game rules. Nothing in here depends on Bevy, so tools, bots and tests
+//! can drive exactly the same game the app runs; `systems` only adapts it to the ECS.
+
+pub mod board;
+pub mod game;
+pub mod grade;
+pub mod gravity;
+pub mod mode;
+pub mod piece;
+pub mod randomizer;
+pub mod rotation;
+pub mod ruleset;
+pub mod scoring;
+pub mod speed;
+pub mod spin;
+
+pub use board::{Board, Cell, BUFFER_ROWS, DEFAULT_HEIGHT, DEFAULT_WIDTH, HEIGHT_RANGE, WIDTH_RANGE};
+pub use game::{random_seed, ActivePiece, Game, GameEvent, HeldButtons, Inputs, Phase, Score};
+pub use grade::{Grade, Grading};
+pub use gravity::{gravity, MAX_GRAVITY};
+pub use mode::{DigGarbage, Ending, Leveling, Mode, ModeKind, StackVisibility};
+pub use piece::{KickTable, PieceDef, PieceSet, PieceSetError, PieceType, TetrominoType};
+pub use randomizer::{Randomizer, RandomizerKind};
+pub use rotation::{RotationKind, RotationSystem};
+pub use ruleset::{Kicks180, LockReset, Ruleset, TopOut, TopOutRules, MAX_PREVIEWS};
+pub use scoring::{LineClear, Scorer, ScoringKind};
+pub use speed::{Delays, SpeedCurve};
+pub use spin::{Kick, TSpin};
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use super::ruleset::TopOut;
+
+/// The built-in game modes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ModeKind {
+    /// Clear a line goal, levelling up every 10 lines.
+    #[default]
+    Marathon,
+    /// Marathon without a goal: play until you top out.
+    Endless,
+    /// Race to clear the line goal (40 by default) as fast as possible.
+    Sprint,
+    /// Score as much as possible before the time limit (2 minutes by default).
+    Ultra,
+    /// Dig out a set number of garbage rows, kept topped up as you clear them.
+    Dig,
+    /// TGM: levels 0-999 by pieces and lines, 20G from level 500, graded at the end.
+    Master,
+}
+
+impl ModeKind {
+    pub const ALL: [ModeKind; 6] = [
+        ModeKind::Marathon,
+        ModeKind::Endless,
+        ModeKind::Sprint,
+        ModeKind::Ultra,
+        ModeKind::Dig,
+        ModeKind::Master,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModeKind::Marathon => "marathon",
+            ModeKind::Endless => "endless",
+            ModeKind::Sprint => "sprint",
+            ModeKind::Ultra => "ultra",
+            ModeKind::Dig => "dig",
+            ModeKind::Master => "master",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// What a game is played for and when it ends, on top of the ruleset.
+/// Each condition is optional, so a mode is a kind plus whichever of them it uses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mode {
+    pub kind: ModeKind,
+    pub start_level: u32,
+    pub leveling: Leveling,
+    // Award a TGM grade
+    pub grading: bool,
+    // Finish as a win once this many lines are cleared
+    pub line_goal: Option<u32>,
+    // Hold the clock until the player first touches a button
+    pub clock_starts_on_input: bool,
+    // Record a split time every this many lines
+    pub split_lines: Option<u32>,
+    // Finish (not a loss) when the clock reaches this
+    pub time_limit: Option<Duration>,
+    pub dig: Option<DigGarbage>,
+    // How much of the locked stack the player gets to see
+    pub stack: StackVisibility,
+    // Big mode: every block is 2×2 cells
+    pub big: bool,
+}
+
+/// Challenge rules for showing the locked stack. Hidden blocks are still on the board,
+/// and the whole stack is shown again once the game ends.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StackVisibility {
+    #[default]
+    Visible,
+    /// Each block fades out, starting `delay` after it locks.
+    Fading { delay: Duration },
+    /// Blocks vanish as soon as they lock.
+    Invisible,
+}
+
+impl StackVisibility {
+    // How long a fading block takes to disappear once it starts
+    pub const FADE_TIME: Duration = Duration::from_millis(500);
+    pub const DEFAULT_FADE_DELAY: Duration = Duration::from_secs(5);
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StackVisibility::Visible => "visible",
+            StackVisibility::Fading { .. } => "fading",
+            StackVisibility::Invisible => "invisible",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "visible" => Some(StackVisibility::Visible),
+            "fading" => Some(StackVisibility::Fading { delay: Self::DEFAULT_FADE_DELAY }),
+            "invisible" => Some(StackVisibility::Invisible),
+            _ => None,
+        }
+    }
+
+    /// How visible a block is `age` after it locked, from 1 (fully shown) to 0 (hidden).
+    pub fn opacity(self, age: Duration) -> f32 {
+        match self {
+            StackVisibility::Visible => 1.0,
+            StackVisibility::Fading { delay } => {
+                let fading = age.saturating_sub(delay).as_secs_f32();
+                1.0 - (fading / Self::FADE_TIME.as_secs_f32()).min(1.0)
+            }
+            StackVisibility::Invisible => 0.0,
+        }
+    }
+}
+
+/// How the level advances.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Leveling {
+    /// One level every 10 lines from the start level.
+    #[default]
+    Lines,
+    /// TGM: one level per piece and one per line cleared. Only a line clear gets past a section
+    /// stop (x99 and the level before the last); reaching `max_level` clears the mode.
+    Sections { max_level: u32 },
+}
+
+impl Leveling {
+    pub const SECTION: u32 = 100;
+}
+
+/// Garbage rows with one hole each, kept at `height` until `total` of them have been cleared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DigGarbage {
+    pub height: u32,
+    pub total: u32,
+    // Chance that a new row's hole is in a different column from the row below (0..=1)
+    pub hole_change: f32,
+}
+
+impl Default for DigGarbage {
+    fn default() -> Self {
+        Self {
+            height: 10,
+            total: 100,
+            hole_change: 0.7,
+        }
+    }
+}
+
+impl Mode {
+    pub const MARATHON_GOAL: u32 = 150;
+    pub const SPRINT_GOAL: u32 = 40;
+    pub const SPRINT_SPLIT: u32 = 10;
+    pub const ULTRA_TIME: Duration = Duration::from_secs(120);
+    pub const MASTER_LEVELS: u32 = 999;
+
+    /// The level a game starts on. Line-based levels count from 1.
+    pub fn first_level(&self) -> u32 {
+        match self.leveling {
+            Leveling::Lines => self.start_level.max(1),
+            Leveling::Sections { .. } => self.start_level,
+        }
+    }
+
+    /// Board cells per block side.
+    pub fn piece_scale(&self) -> i32 {
+        if self.big { 2 } else { 1 }
+    }
+
+    /// The standard setup for `kind`.
+    pub fn new(kind: ModeKind) -> Self {
+        let endless = Self {
+            kind,
+            start_level: 1,
+            leveling: Leveling::Lines,
+            grading: false,
+            line_goal: None,
+            clock_starts_on_input: false,
+            split_lines: None,
+            time_limit: None,
+            dig: None,
+            stack: StackVisibility::Visible,
+            big: false,
+        };
+        match kind {
+            ModeKind::Marathon => Self {
+                line_goal: Some(Self::MARATHON_GOAL),
+                ..endless
+            },
+            ModeKind::Endless => endless,
+            ModeKind::Sprint => Self {
+                line_goal: Some(Self::SPRINT_GOAL),
+                clock_starts_on_input: true,
+                split_lines: Some(Self::SPRINT_SPLIT),
+                ..endless
+            },
+            ModeKind::Ultra => Self {
+                time_limit: Some(Self::ULTRA_TIME),
+                ..endless
+            },
+            ModeKind::Dig => Self {
+                dig: Some(DigGarbage::default()),
+                ..endless
+            },
+            ModeKind::Master => Self {
+                start_level: 0,
+                leveling: Leveling::Sections { max_level: Self::MASTER_LEVELS },
+                grading: true,
+                ..endless
+            },
+        }
+    }
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Self::new(ModeKind::default())
+    }
+}
+
+/// How a game ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending {
+    TopOut(TopOut),
+    /// Reached the mode's goal.
+    Cleared,
+    /// The mode's time limit ran out.
+    TimeUp,
+}
+
+impl Ending {
+    /// For the results screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Ending::TopOut(reason) => reason.label(),
+            Ending::Cleared => "Cleared!",
+            Ending::TimeUp => "Time up!",
+        }
+    }
+
+    pub fn is_win(self) -> bool {
+        !matches!(self, Ending::TopOut(_))
+    }
+}
@@ -0,0 +1,325 @@
+// Piece definitions, loaded from data. Kept free of Bevy types so the engine can run headless.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use super::ruleset::Kicks180;
+
+/// One piece as a set defines it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PieceDef {
+    pub name: String,
+    // sRGB, 0 to 1
+    pub color: (f32, f32, f32),
+    // Block offsets from the pivot for each rotation state, clockwise from spawn.
+    // One state means the piece doesn't turn; two or four are cycled through.
+    pub rotations: Vec<Vec<(i32, i32)>>,
+    pub kicks: KickTable,
+    // Moves the spawn position from the default (centred, just above the visible rows)
+    #[serde(default)]
+    pub spawn_offset: (i32, i32),
+}
+
+/// Offsets tried in order when a piece turns a quarter; the first one that fits wins.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum KickTable {
+    /// Turn in place or not at all.
+    None,
+    /// The SRS table for J, L, S, T and Z.
+    Srs,
+    /// The SRS table for I.
+    SrsI,
+    /// Kicks for each starting rotation state, turning clockwise and counter-clockwise.
+    Custom {
+        cw: Vec<Vec<(i32, i32)>>,
+        ccw: Vec<Vec<(i32, i32)>>,
+    },
+}
+
+/// A piece from a loaded set. Cheap to copy; two are equal if they share a definition.
+#[derive(Clone, Copy)]
+pub struct PieceType(&'static PieceDef);
+
+impl PieceType {
+    pub fn def(self) -> &'static PieceDef {
+        self.0
+    }
+
+    pub fn name(self) -> &'static str {
+        &self.0.name
+    }
+
+    pub fn color(self) -> (f32, f32, f32) {
+        self.0.color
+    }
+
+    pub fn spawn_offset(self) -> (i32, i32) {
+        self.0.spawn_offset
+    }
+
+    /// Block offsets from the pivot in rotation state `rotation` (0 = spawn).
+    pub fn offsets(self, rotation: usize) -> &'static [(i32, i32)] {
+        let rotations = &self.0.rotations;
+        &rotations[rotation % rotations.len()]
+    }
+
+    /// Kicks for a quarter turn from `old_rot` to `new_rot`.
+    pub fn kicks(self, old_rot: usize, new_rot: usize) -> Vec<(i32, i32)> {
+        let k = (old_rot % 4, new_rot % 4);
+        match &self.0.kicks {
+            KickTable::None => vec![(0, 0)],
+            KickTable::SrsI => match k {
+                (0, 1) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (1, 0) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (1, 2) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                (2, 1) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (2, 3) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (3, 2) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (3, 0) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (0, 3) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                _ => vec![(0, 0)],
+            },
+            KickTable::Srs => match k {
+                (0, 1) => vec![(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+                (1, 0) => vec![(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+                (1, 2) => vec![(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+                (2, 1) => vec![(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+                (2, 3) => vec![(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+                (3, 2) => vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+                (3, 0) => vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+                (0, 3) => vec![(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+                _ => vec![(0, 0)],
+            },
+            KickTable::Custom { cw, ccw } => {
+                let table = if k.1 == (k.0 + 1) % 4 { cw } else { ccw };
+                match table.get(k.0 % table.len().max(1)) {
+                    Some(kicks) if !kicks.is_empty() => kicks.clone(),
+                    _ => vec![(0, 0)],
+                }
+            }
+        }
+    }
+
+    /// 180° kicks. SRS itself has none; SRS+ (TETR.IO) uses the same table for every piece that kicks.
+    pub fn kicks_180(self, old_rot: usize, table: Kicks180) -> Vec<(i32, i32)> {
+        if table == Kicks180::None || self.0.kicks == KickTable::None {
+            return vec![(0, 0)];
+        }
+        match old_rot % 4 {
+            0 => vec![(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+            1 => vec![(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+            2 => vec![(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+            3 => vec![(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl PartialEq for PieceType {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for PieceType {}
+
+impl Hash for PieceType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state);
+    }
+}
+
+impl fmt::Debug for PieceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The classic tetrominoes, for rules written against them (ARS and NRS shapes, T-spins,
+/// the TGM randomizer). Pieces are matched by name, so any set can opt in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TetrominoType {
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z,
+}
+
+impl TetrominoType {
+    pub const ALL: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::J,
+        TetrominoType::L,
+        TetrominoType::O,
+        TetrominoType::S,
+        TetrominoType::T,
+        TetrominoType::Z,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TetrominoType::I => "I",
+            TetrominoType::J => "J",
+            TetrominoType::L => "L",
+            TetrominoType::O => "O",
+            TetrominoType::S => "S",
+            TetrominoType::T => "T",
+            TetrominoType::Z => "Z",
+        }
+    }
+
+    /// The tetromino `piece` is, if it has four blocks and one of these names.
+    pub fn of(piece: PieceType) -> Option<Self> {
+        if piece.offsets(0).len() != 4 {
+            return None;
+        }
+        Self::ALL.into_iter().find(|tetromino| tetromino.name() == piece.name())
+    }
+}
+
+/// Why a piece set couldn't be loaded.
+#[derive(Debug)]
+pub enum PieceSetError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for PieceSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PieceSetError::Io(err) => write!(f, "{}", err),
+            PieceSetError::Parse(err) => write!(f, "{}", err),
+            PieceSetError::Invalid(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for PieceSetError {}
+
+/// The pieces a game is played with, as loaded from a RON file.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PieceSet {
+    pub name: String,
+    pub pieces: Vec<PieceDef>,
+}
+
+impl PieceSet {
+    /// Sets that ship with the game, by name.
+    pub const BUNDLED: [&'static str; 3] = ["tetromino", "pentomino", "mini"];
+
+    pub fn bundled(name: &str) -> Option<&'static PieceSet> {
+        static TETROMINO: OnceLock<PieceSet> = OnceLock::new();
+        static PENTOMINO: OnceLock<PieceSet> = OnceLock::new();
+        static MINI: OnceLock<PieceSet> = OnceLock::new();
+        let (set, text) = match name {
+            "tetromino" => (&TETROMINO, include_str!("../../assets/pieces/tetromino.ron")),
+            "pentomino" => (&PENTOMINO, include_str!("../../assets/pieces/pentomino.ron")),
+            "mini" => (&MINI, include_str!("../../assets/pieces/mini.ron")),
+            _ => return None,
+        };
+        Some(set.get_or_init(|| Self::from_ron(text).expect("bundled piece sets are valid")))
+    }
+
+    /// The standard seven.
+    pub fn tetromino() -> &'static PieceSet {
+        Self::bundled("tetromino").unwrap()
+    }
+
+    /// Reads a set from a RON file. Sets live for the rest of the program, so load each one once.
+    pub fn load(path: impl AsRef<Path>) -> Result<&'static PieceSet, PieceSetError> {
+        let text = std::fs::read_to_string(path).map_err(PieceSetError::Io)?;
+        Ok(Box::leak(Box::new(Self::from_ron(&text)?)))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, PieceSetError> {
+        let set: PieceSet = ron::from_str(text).map_err(PieceSetError::Parse)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    fn validate(&self) -> Result<(), PieceSetError> {
+        if self.pieces.is_empty() {
+            return Err(PieceSetError::Invalid(format!("piece set '{}' has no pieces", self.name)));
+        }
+        for piece in &self.pieces {
+            let blocks = piece.rotations.first().map_or(0, |cells| cells.len());
+            let valid = blocks > 0
+                && matches!(piece.rotations.len(), 1 | 2 | 4)
+                && piece.rotations.iter().all(|cells| cells.len() == blocks);
+            if !valid {
+                return Err(PieceSetError::Invalid(format!(
+                    "piece '{}' needs 1, 2 or 4 rotation states with the same number of blocks in each",
+                    piece.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every piece in the set, in order.
+    pub fn types(&'static self) -> Vec<PieceType> {
+        self.pieces.iter().map(PieceType).collect()
+    }
+
+    pub fn find(&'static self, name: &str) -> Option<PieceType> {
+        self.pieces.iter().find(|piece| piece.name == name).map(PieceType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_with(rotations: &str) -> String {
+        format!("(name: \"test\", pieces: [(name: \"X\", color: (1.0, 1.0, 1.0), rotations: {}, kicks: Srs)])", rotations)
+    }
+
+    #[test]
+    fn bundled_sets_load() {
+        let counts: Vec<usize> = PieceSet::BUNDLED.iter().map(|name| PieceSet::bundled(name).unwrap().pieces.len()).collect();
+        assert_eq!(counts, [7, 18, 3]);
+        assert!(PieceSet::bundled("heptomino").is_none());
+    }
+
+    #[test]
+    fn a_set_with_one_two_or_four_states_is_valid() {
+        for rotations in ["[[(0, 0)]]", "[[(0, 0), (1, 0)], [(0, 0), (0, 1)]]", "[[(0, 0)], [(0, 0)], [(0, 0)], [(0, 0)]]"] {
+            assert!(PieceSet::from_ron(&set_with(rotations)).is_ok(), "{}", rotations);
+        }
+    }
+
+    #[test]
+    fn malformed_ron_is_a_parse_error() {
+        assert!(matches!(PieceSet::from_ron("(name: \"test\", pieces: [(name: \"X\""), Err(PieceSetError::Parse(_))));
+    }
+
+    #[test]
+    fn a_set_without_pieces_is_invalid() {
+        assert!(matches!(PieceSet::from_ron("(name: \"empty\", pieces: [])"), Err(PieceSetError::Invalid(_))));
+    }
+
+    #[test]
+    fn pieces_need_matching_block_counts_and_one_two_or_four_states() {
+        for rotations in [
+            "[]",
+            "[[]]",
+            "[[(0, 0), (1, 0)], [(0, 0)]]",
+            "[[(0, 0)], [(0, 0)], [(0, 0)]]",
+        ] {
+            assert!(matches!(PieceSet::from_ron(&set_with(rotations)), Err(PieceSetError::Invalid(_))), "{}", rotations);
+        }
+    }
+
+    #[test]
+    fn loading_a_missing_file_is_an_io_error() {
+        assert!(matches!(PieceSet::load("no/such/pieces.ron"), Err(PieceSetError::Io(_))));
+    }
+}
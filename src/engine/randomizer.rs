@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use super::piece::{PieceType, TetrominoType};
+
+/// Decides which piece comes next. The game owns the RNG and the piece set and lends them out,
+/// so a randomizer only holds its own history/bag state.
+pub trait Randomizer: Debug + Send + Sync {
+    /// Picks from `pieces`, which is never empty.
+    fn next_piece(&mut self, rng: &mut ChaCha8Rng, pieces: &[PieceType]) -> PieceType;
+
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The built-in randomizers, for picking one at game start.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    PureRandom,
+    #[default]
+    SevenBag,
+    FourteenBag,
+    TgmHistory,
+    NesReroll,
+}
+
+impl RandomizerKind {
+    pub const ALL: [RandomizerKind; 5] = [
+        RandomizerKind::PureRandom,
+        RandomizerKind::SevenBag,
+        RandomizerKind::FourteenBag,
+        RandomizerKind::TgmHistory,
+        RandomizerKind::NesReroll,
+    ];
+
+    pub fn build(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::PureRandom => Box::new(PureRandom),
+            RandomizerKind::SevenBag => Box::new(Bag::new(1)),
+            RandomizerKind::FourteenBag => Box::new(Bag::new(2)),
+            RandomizerKind::TgmHistory => Box::new(TgmHistory::default()),
+            RandomizerKind::NesReroll => Box::new(NesReroll::default()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomizerKind::PureRandom => "random",
+            RandomizerKind::SevenBag => "7bag",
+            RandomizerKind::FourteenBag => "14bag",
+            RandomizerKind::TgmHistory => "tgm",
+            RandomizerKind::NesReroll => "nes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// Uniform choice every time; droughts and floods included.
+#[derive(Clone, Debug)]
+pub struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next_piece(&mut self, rng: &mut ChaCha8Rng, pieces: &[PieceType]) -> PieceType {
+        *pieces.choose(rng).unwrap()
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Deals shuffled bags holding `copies` of each piece (1 = 7-bag, 2 = 14-bag, for tetrominoes).
+#[derive(Clone, Debug)]
+pub struct Bag {
+    copies: usize,
+    remaining: Vec<PieceType>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Self {
+            copies: copies.max(1),
+            remaining: Vec::new(),
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next_piece(&mut self, rng: &mut ChaCha8Rng, pieces: &[PieceType]) -> PieceType {
+        if self.remaining.is_empty() {
+            for _ in 0..self.copies {
+                self.remaining.extend(pieces);
+            }
+            self.remaining.shuffle(rng);
+        }
+        self.remaining.pop().unwrap()
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// TGM2-style: remember the last 4 pieces and reroll up to 6 times to avoid them.
+/// The history starts as Z, S, Z, S and the first piece is never S, Z or O (where the set has them).
+#[derive(Clone, Debug)]
+pub struct TgmHistory {
+    history: VecDeque<PieceType>,
+    first: bool,
+}
+
+impl TgmHistory {
+    const HISTORY: usize = 4;
+    const ROLLS: usize = 6;
+}
+
+impl Default for TgmHistory {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(Self::HISTORY),
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for TgmHistory {
+    fn next_piece(&mut self, rng: &mut ChaCha8Rng, pieces: &[PieceType]) -> PieceType {
+        use TetrominoType::{O, S, Z};
+        let is = |piece: PieceType, tetromino: TetrominoType| TetrominoType::of(piece) == Some(tetromino);
+        let piece = if self.first {
+            self.first = false;
+            for tetromino in [Z, S, Z, S] {
+                self.history.extend(pieces.iter().find(|&&piece| is(piece, tetromino)));
+            }
+            let openers: Vec<PieceType> =
+                pieces.iter().copied().filter(|&piece| ![S, Z, O].into_iter().any(|t| is(piece, t))).collect();
+            *openers.choose(rng).or_else(|| pieces.choose(rng)).unwrap()
+        } else {
+            let mut piece = *pieces.choose(rng).unwrap();
+            for _ in 1..Self::ROLLS {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = *pieces.choose(rng).unwrap();
+            }
+            piece
+        };
+        if self.history.len() >= Self::HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(piece);
+        piece
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// NES: roll a die with a side per piece plus a dummy (8 sides for tetrominoes); on the dummy side
+/// or a repeat of the last piece, roll once more.
+#[derive(Clone, Debug, Default)]
+pub struct NesReroll {
+    last: Option<PieceType>,
+}
+
+impl Randomizer for NesReroll {
+    fn next_piece(&mut self, rng: &mut ChaCha8Rng, pieces: &[PieceType]) -> PieceType {
+        let roll = rng.random_range(0..pieces.len() + 1);
+        let piece = match pieces.get(roll) {
+            Some(&piece) if Some(piece) != self.last => piece,
+            _ => *pieces.choose(rng).unwrap(),
+        };
+        self.last = Some(piece);
+        piece
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::engine::piece::PieceSet;
+
+    #[test]
+    fn seven_bag_deals_every_piece_once_per_bag() {
+        let pieces = PieceSet::tetromino().types();
+        for seed in 0..20 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let mut randomizer = RandomizerKind::SevenBag.build();
+            for _ in 0..10 {
+                let bag: HashSet<PieceType> = (0..7).map(|_| randomizer.next_piece(&mut rng, &pieces)).collect();
+                assert_eq!(bag.len(), 7);
+            }
+        }
+    }
+
+    #[test]
+    fn tgm_never_opens_with_s_z_or_o() {
+        let pieces = PieceSet::tetromino().types();
+        for seed in 0..100 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let first = RandomizerKind::TgmHistory.build().next_piece(&mut rng, &pieces);
+            assert!(!["S", "Z", "O"].contains(&first.name()), "seed {} opened with {:?}", seed, first);
+        }
+    }
+}
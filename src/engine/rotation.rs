@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+
+use super::board::Board;
+use super::game::ActivePiece;
+use super::piece::{PieceType, TetrominoType};
+use super::ruleset::Kicks180;
+
+/// Piece orientations and the kicks tried when turning between them.
+/// Systems are stateless, so pieces refer to theirs by `RotationKind`.
+pub trait RotationSystem: Debug + Send + Sync {
+    /// Block offsets from the pivot for `piece_type` in rotation state `rotation` (0 = spawn).
+    fn offsets(&self, piece_type: PieceType, rotation: usize) -> Vec<(i32, i32)>;
+
+    /// Offsets to try, in order, when turning `piece` by `turns` quarter turns clockwise.
+    /// The first one that fits wins; none fitting means the rotation fails.
+    /// Kicks are in blocks, so Big mode pieces kick twice as far.
+    fn kicks(&self, board: &Board, piece: &ActivePiece, turns: usize, kicks_180: Kicks180) -> Vec<(i32, i32)>;
+}
+
+/// The built-in rotation systems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RotationKind {
+    /// Super Rotation System, as in the guideline.
+    #[default]
+    Srs,
+    /// Arika Rotation System (TGM).
+    Ars,
+    /// Nintendo Rotation System (NES).
+    Nrs,
+}
+
+impl RotationKind {
+    pub const ALL: [RotationKind; 3] = [RotationKind::Srs, RotationKind::Ars, RotationKind::Nrs];
+
+    pub fn system(self) -> &'static dyn RotationSystem {
+        match self {
+            RotationKind::Srs => &Srs,
+            RotationKind::Ars => &Ars,
+            RotationKind::Nrs => &Nrs,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RotationKind::Srs => "srs",
+            RotationKind::Ars => "ars",
+            RotationKind::Nrs => "nrs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// SRS, or rather the piece set's own data: shapes and kick tables as each piece defines them.
+/// For the tetromino set that is standard SRS, flat side down with the usual wall kicks.
+#[derive(Debug)]
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn offsets(&self, piece_type: PieceType, rotation: usize) -> Vec<(i32, i32)> {
+        piece_type.offsets(rotation).to_vec()
+    }
+
+    fn kicks(&self, _board: &Board, piece: &ActivePiece, turns: usize, kicks_180: Kicks180) -> Vec<(i32, i32)> {
+        let new_rot = (piece.rotation_state + turns) % 4;
+        if turns % 4 == 2 {
+            piece.piece_type.kicks_180(piece.rotation_state, kicks_180)
+        } else {
+            piece.piece_type.kicks(piece.rotation_state, new_rot)
+        }
+    }
+}
+
+/// ARS: pieces spawn flat side up, every state rests on the bottom of its box, and a failed
+/// rotation tries one cell right then one cell left. I never kicks, and L, J and T don't kick
+/// when the first blocked cell (top to bottom, left to right) is in the centre column.
+/// Pieces other than the seven tetrominoes keep their set's shapes.
+#[derive(Debug)]
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn offsets(&self, piece_type: PieceType, rotation: usize) -> Vec<(i32, i32)> {
+        use TetrominoType::*;
+        let Some(tetromino) = TetrominoType::of(piece_type) else { return Srs.offsets(piece_type, rotation) };
+        let offsets = match (tetromino, rotation % 4) {
+            (I, 0 | 2) => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+            (I, _) => [(1, 1), (1, 0), (1, -1), (1, -2)],
+            (O, _) => [(0, 0), (1, 0), (0, -1), (1, -1)],
+            (T, 0) => [(-1, 0), (0, 0), (1, 0), (0, -1)],
+            (T, 1) => [(0, 1), (-1, 0), (0, 0), (0, -1)],
+            (T, 2) => [(0, 0), (-1, -1), (0, -1), (1, -1)],
+            (T, _) => [(0, 1), (0, 0), (1, 0), (0, -1)],
+            (J, 0) => [(-1, 0), (0, 0), (1, 0), (1, -1)],
+            (J, 1) => [(0, 1), (0, 0), (-1, -1), (0, -1)],
+            (J, 2) => [(-1, 0), (-1, -1), (0, -1), (1, -1)],
+            (J, _) => [(0, 1), (1, 1), (0, 0), (0, -1)],
+            (L, 0) => [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+            (L, 1) => [(-1, 1), (0, 1), (0, 0), (0, -1)],
+            (L, 2) => [(1, 0), (-1, -1), (0, -1), (1, -1)],
+            (L, _) => [(0, 1), (0, 0), (0, -1), (1, -1)],
+            (S, 0 | 2) => [(0, 0), (1, 0), (-1, -1), (0, -1)],
+            (S, _) => [(-1, 1), (-1, 0), (0, 0), (0, -1)],
+            (Z, 0 | 2) => [(-1, 0), (0, 0), (0, -1), (1, -1)],
+            (Z, _) => [(1, 1), (0, 0), (1, 0), (0, -1)],
+        };
+        offsets.to_vec()
+    }
+
+    fn kicks(&self, board: &Board, piece: &ActivePiece, turns: usize, _kicks_180: Kicks180) -> Vec<(i32, i32)> {
+        use TetrominoType::*;
+        match TetrominoType::of(piece.piece_type) {
+            Some(I | O) => return vec![(0, 0)],
+            Some(L | J | T) => {
+                let rotated = ActivePiece {
+                    rotation_state: (piece.rotation_state + turns) % 4,
+                    ..*piece
+                };
+                let first_blocked = rotated
+                    .cells()
+                    .into_iter()
+                    .filter(|&(x, y)| !board.is_valid_position(x, y))
+                    .min_by_key(|&(x, y)| (-y, x));
+                if first_blocked.is_some_and(|(x, _)| (x - piece.x).div_euclid(piece.scale) == 0) {
+                    return vec![(0, 0)];
+                }
+            }
+            _ => {}
+        }
+        vec![(0, 0), (1, 0), (-1, 0)]
+    }
+}
+
+/// NRS: NES orientations (flat side up at spawn, S, Z and I with two states) and no kicks at all.
+/// Pieces other than the seven tetrominoes keep their set's shapes.
+#[derive(Debug)]
+pub struct Nrs;
+
+impl RotationSystem for Nrs {
+    fn offsets(&self, piece_type: PieceType, rotation: usize) -> Vec<(i32, i32)> {
+        use TetrominoType::*;
+        let Some(tetromino) = TetrominoType::of(piece_type) else { return Srs.offsets(piece_type, rotation) };
+        let spawn = match tetromino {
+            I => match rotation % 2 {
+                0 => return vec![(-2, 0), (-1, 0), (0, 0), (1, 0)],
+                _ => return vec![(0, 2), (0, 1), (0, 0), (0, -1)],
+            },
+            O => return vec![(-1, 0), (0, 0), (-1, -1), (0, -1)],
+            S => match rotation % 2 {
+                0 => return vec![(0, 0), (1, 0), (-1, -1), (0, -1)],
+                _ => return vec![(0, 1), (0, 0), (1, 0), (1, -1)],
+            },
+            Z => match rotation % 2 {
+                0 => return vec![(-1, 0), (0, 0), (0, -1), (1, -1)],
+                _ => return vec![(1, 1), (0, 0), (1, 0), (0, -1)],
+            },
+            T => [(-1, 0), (0, 0), (1, 0), (0, -1)],
+            J => [(-1, 0), (0, 0), (1, 0), (1, -1)],
+            L => [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+        };
+        // J, L and T turn about their centre block
+        let mut offsets = spawn;
+        for _ in 0..rotation % 4 {
+            offsets = offsets.map(|(x, y)| (y, -x));
+        }
+        offsets.to_vec()
+    }
+
+    fn kicks(&self, _board: &Board, _piece: &ActivePiece, _turns: usize, _kicks_180: Kicks180) -> Vec<(i32, i32)> {
+        vec![(0, 0)]
+    }
+}
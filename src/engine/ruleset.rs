@@ -0,0 +1,255 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use super::board::{BUFFER_ROWS, Board, DEFAULT_HEIGHT, DEFAULT_WIDTH, HEIGHT_RANGE, WIDTH_RANGE};
+use super::mode::{Mode, ModeKind};
+use super::piece::PieceSet;
+use super::randomizer::RandomizerKind;
+use super::rotation::RotationKind;
+use super::scoring::ScoringKind;
+use super::speed::SpeedCurve;
+
+pub const MAX_PREVIEWS: usize = 6;
+
+/// What buys a grounded piece more time before it locks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockReset {
+    /// Any successful move or rotate restarts the delay, up to `max_lock_resets` times.
+    #[default]
+    Move,
+    /// Only reaching a new lowest row restarts the delay.
+    Step,
+    /// The delay runs down once per piece, whatever the player does.
+    None,
+}
+
+impl LockReset {
+    pub fn name(self) -> &'static str {
+        match self {
+            LockReset::Move => "move",
+            LockReset::Step => "step",
+            LockReset::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [LockReset::Move, LockReset::Step, LockReset::None]
+            .into_iter()
+            .find(|policy| policy.name() == name)
+    }
+}
+
+/// Kick table used for 180° rotation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kicks180 {
+    /// Rotate in place or not at all.
+    None,
+    /// TETR.IO's SRS+ table.
+    #[default]
+    SrsPlus,
+}
+
+impl Kicks180 {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kicks180::None => "none",
+            Kicks180::SrsPlus => "srs+",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Kicks180::None, Kicks180::SrsPlus]
+            .into_iter()
+            .find(|table| table.name() == name)
+    }
+}
+
+/// Why a game ended by topping out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TopOut {
+    /// The next piece's spawn position is blocked.
+    BlockOut,
+    /// A piece locked entirely above the visible field.
+    LockOut,
+    /// A piece locked with any of its blocks above the visible field.
+    PartialLockOut,
+    /// Rising garbage pushed blocks off the top of the buffer.
+    GarbageOut,
+}
+
+impl TopOut {
+    pub const ALL: [TopOut; 4] = [TopOut::BlockOut, TopOut::LockOut, TopOut::PartialLockOut, TopOut::GarbageOut];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TopOut::BlockOut => "block",
+            TopOut::LockOut => "lock",
+            TopOut::PartialLockOut => "partial",
+            TopOut::GarbageOut => "garbage",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| reason.name() == name)
+    }
+
+    /// For the game-over screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            TopOut::BlockOut => "Block out",
+            TopOut::LockOut => "Lock out",
+            TopOut::PartialLockOut => "Partial lock out",
+            TopOut::GarbageOut => "Garbage out",
+        }
+    }
+}
+
+/// Which top-out conditions end the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopOutRules {
+    // Off: a blocked spawn moves up into the buffer until it fits (and only ends the game if nothing does)
+    pub block_out: bool,
+    pub lock_out: bool,
+    pub partial_lock_out: bool,
+    // Off: blocks pushed off the top by garbage are simply lost
+    pub garbage_out: bool,
+}
+
+impl TopOutRules {
+    /// No conditions at all; enable them one by one with `set`.
+    pub const NONE: TopOutRules = TopOutRules {
+        block_out: false,
+        lock_out: false,
+        partial_lock_out: false,
+        garbage_out: false,
+    };
+
+    pub fn enabled(&self, reason: TopOut) -> bool {
+        match reason {
+            TopOut::BlockOut => self.block_out,
+            TopOut::LockOut => self.lock_out,
+            TopOut::PartialLockOut => self.partial_lock_out,
+            TopOut::GarbageOut => self.garbage_out,
+        }
+    }
+
+    pub fn set(&mut self, reason: TopOut, enabled: bool) {
+        match reason {
+            TopOut::BlockOut => self.block_out = enabled,
+            TopOut::LockOut => self.lock_out = enabled,
+            TopOut::PartialLockOut => self.partial_lock_out = enabled,
+            TopOut::GarbageOut => self.garbage_out = enabled,
+        }
+    }
+}
+
+impl Default for TopOutRules {
+    /// The guideline: everything except partial lock out.
+    fn default() -> Self {
+        Self {
+            block_out: true,
+            lock_out: true,
+            partial_lock_out: false,
+            garbage_out: true,
+        }
+    }
+}
+
+/// Everything about the rules that is chosen when a game starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ruleset {
+    pub mode: Mode,
+    pub randomizer: RandomizerKind,
+    // How many upcoming pieces are revealed (1..=MAX_PREVIEWS)
+    pub previews: usize,
+    // How long a piece may rest on the stack before it locks
+    pub lock_delay: Duration,
+    pub lock_reset: LockReset,
+    pub max_lock_resets: u32,
+    // Entry delay: how long the next piece is withheld after a lock
+    pub are: Duration,
+    // How long cleared rows stay on show before the stack drops (ARE follows)
+    pub line_clear_delay: Duration,
+    // Level-dependent gravity, and delays that override the fixed ones above
+    pub speed: SpeedCurve,
+    pub rotation: RotationKind,
+    // Initial rotation/hold: buttons held as a piece spawns act on it before it appears
+    pub irs: bool,
+    pub ihs: bool,
+    // Only buttons pressed after the last lock count for IRS/IHS, so one still down from the
+    // previous piece can't turn or hold the next by accident (matters most with no ARE)
+    pub fresh_initial: bool,
+    // Only SRS uses a 180° kick table
+    pub kicks_180: Kicks180,
+    pub scoring: ScoringKind,
+    // Board size in cells (see `WIDTH_RANGE` and `HEIGHT_RANGE`)
+    pub width: i32,
+    // Visible rows
+    pub height: i32,
+    // Hidden rows above the visible ones
+    pub buffer_rows: i32,
+    pub top_out: TopOutRules,
+    // What the randomizer deals; sets live for the whole program
+    pub pieces: &'static PieceSet,
+}
+
+impl Ruleset {
+    /// The rules `mode` is meant to be played with: guideline rules, except Master which plays like TGM.
+    pub fn for_mode(mode: Mode) -> Self {
+        match mode.kind {
+            ModeKind::Master => Self {
+                randomizer: RandomizerKind::TgmHistory,
+                previews: 3,
+                lock_reset: LockReset::Step,
+                speed: SpeedCurve::master(),
+                rotation: RotationKind::Ars,
+                scoring: ScoringKind::Tgm,
+                ..Self::guideline(mode)
+            },
+            _ => Self::guideline(mode),
+        }
+    }
+
+    /// The board these rules play on. Big mode works in whole blocks, so each size is
+    /// rounded down to a multiple of the block size and the field kept four blocks wide.
+    pub fn board(&self) -> Board {
+        let scale = self.mode.piece_scale();
+        let blocks = |cells: i32, range: RangeInclusive<i32>| cells.clamp(*range.start(), *range.end()) / scale * scale;
+        Board::new(
+            blocks(self.width, WIDTH_RANGE).max(WIDTH_RANGE.start() * scale),
+            blocks(self.height, HEIGHT_RANGE),
+            self.buffer_rows.max(0) / scale * scale,
+        )
+    }
+
+    fn guideline(mode: Mode) -> Self {
+        Self {
+            mode,
+            randomizer: RandomizerKind::default(),
+            previews: 5,
+            lock_delay: Duration::from_millis(500),
+            lock_reset: LockReset::Move,
+            max_lock_resets: 15,
+            are: Duration::ZERO,
+            line_clear_delay: Duration::ZERO,
+            speed: SpeedCurve::default(),
+            rotation: RotationKind::default(),
+            irs: true,
+            ihs: true,
+            fresh_initial: false,
+            kicks_180: Kicks180::default(),
+            scoring: ScoringKind::default(),
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            buffer_rows: BUFFER_ROWS,
+            top_out: TopOutRules::default(),
+            pieces: PieceSet::tetromino(),
+        }
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::for_mode(Mode::default())
+    }
+}
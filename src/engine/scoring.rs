@@ -0,0 +1,263 @@
+use std::fmt::Debug;
+
+use super::spin::TSpin;
+
+/// One lock that cleared lines or was a T-spin, as the scorer sees it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineClear {
+    pub lines: u32,
+    pub t_spin: TSpin,
+    // The board was left empty
+    pub perfect_clear: bool,
+    // Consecutive line-clearing locks including this one (1 = no combo yet)
+    pub combo: u32,
+    // Lines cleared over those locks
+    pub combo_lines: u32,
+    // This clear is a tetris or spin that continues a streak of them
+    pub back_to_back: bool,
+    // Blocks the player dropped the piece by, soft, sonic or hard
+    pub dropped: u32,
+    // The level once this lock has been counted
+    pub level_after: u32,
+}
+
+impl LineClear {
+    /// Tetrises and spins that clear lines; these build back-to-back streaks.
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.t_spin != TSpin::None && self.lines > 0)
+    }
+}
+
+/// Turns clears and drops into points. The game tracks combos and streaks;
+/// a scorer only decides what they are worth.
+pub trait Scorer: Debug + Send + Sync {
+    fn line_clear(&self, clear: &LineClear, level: u32) -> u32;
+
+    fn soft_drop(&self, cells: u32) -> u32;
+
+    fn hard_drop(&self, cells: u32) -> u32;
+
+    fn clone_box(&self) -> Box<dyn Scorer>;
+}
+
+impl Clone for Box<dyn Scorer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The built-in scoring tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoringKind {
+    #[default]
+    Guideline,
+    Nes,
+    Tgm,
+}
+
+impl ScoringKind {
+    pub const ALL: [ScoringKind; 3] = [ScoringKind::Guideline, ScoringKind::Nes, ScoringKind::Tgm];
+
+    pub fn build(self) -> Box<dyn Scorer> {
+        match self {
+            ScoringKind::Guideline => Box::new(GuidelineScorer),
+            ScoringKind::Nes => Box::new(NesScorer),
+            ScoringKind::Tgm => Box::new(TgmScorer),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoringKind::Guideline => "guideline",
+            ScoringKind::Nes => "nes",
+            ScoringKind::Tgm => "tgm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// Modern guideline: spins, combos, back-to-back and perfect clears, all scaled by level.
+#[derive(Clone, Debug)]
+pub struct GuidelineScorer;
+
+impl GuidelineScorer {
+    const COMBO_POINTS: u32 = 50;
+}
+
+impl Scorer for GuidelineScorer {
+    fn line_clear(&self, clear: &LineClear, level: u32) -> u32 {
+        let base = match (clear.t_spin, clear.lines) {
+            (TSpin::None, 0) => 0,
+            (TSpin::None, 1) => 100,
+            (TSpin::None, 2) => 300,
+            (TSpin::None, 3) => 500,
+            (TSpin::None, _) => 800,
+            (TSpin::Mini, 0) => 100,
+            (TSpin::Mini, 1) => 200,
+            (TSpin::Mini, _) => 400,
+            (TSpin::Full, 0) => 400,
+            (TSpin::Full, 1) => 800,
+            (TSpin::Full, 2) => 1200,
+            (TSpin::Full, _) => 1600,
+        };
+        // Back-to-back is worth half as much again
+        let base = if clear.back_to_back { base * 3 / 2 } else { base };
+        let combo = Self::COMBO_POINTS * clear.combo.saturating_sub(1);
+        let perfect_clear = match (clear.perfect_clear, clear.lines) {
+            (false, _) | (true, 0) => 0,
+            (true, 1) => 800,
+            (true, 2) => 1200,
+            (true, 3) => 1800,
+            (true, _) if clear.back_to_back => 3200,
+            (true, _) => 2000,
+        };
+        (base + combo + perfect_clear) * level
+    }
+
+    fn soft_drop(&self, cells: u32) -> u32 {
+        cells
+    }
+
+    fn hard_drop(&self, cells: u32) -> u32 {
+        2 * cells
+    }
+
+    fn clone_box(&self) -> Box<dyn Scorer> {
+        Box::new(self.clone())
+    }
+}
+
+/// NES: a flat table by line count, times the level. Spins, streaks and hard drops earn nothing.
+#[derive(Clone, Debug)]
+pub struct NesScorer;
+
+impl Scorer for NesScorer {
+    fn line_clear(&self, clear: &LineClear, level: u32) -> u32 {
+        // NES levels start at 0, so its (level + 1) is our level
+        let base = match clear.lines {
+            0 => 0,
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200,
+        };
+        base * level
+    }
+
+    fn soft_drop(&self, cells: u32) -> u32 {
+        cells
+    }
+
+    fn hard_drop(&self, _cells: u32) -> u32 {
+        0
+    }
+
+    fn clone_box(&self) -> Box<dyn Scorer> {
+        Box::new(self.clone())
+    }
+}
+
+/// TGM2: `(ceil((level + lines) / 4) + dropped) * lines * combo * bravo + ceil(level after / 2)`.
+/// Combo starts at 1 and each clear in it adds `2 * lines - 2`; bravo is 4 for a perfect clear.
+/// Levels start at 0 here. Drops only count towards a clear, so they earn nothing by themselves.
+#[derive(Clone, Debug)]
+pub struct TgmScorer;
+
+impl Scorer for TgmScorer {
+    fn line_clear(&self, clear: &LineClear, level: u32) -> u32 {
+        if clear.lines == 0 {
+            return 0;
+        }
+        let combo = 1 + 2 * clear.combo_lines - 2 * clear.combo;
+        let bravo = if clear.perfect_clear { 4 } else { 1 };
+        ((level + clear.lines).div_ceil(4) + clear.dropped) * clear.lines * combo * bravo + clear.level_after.div_ceil(2)
+    }
+
+    fn soft_drop(&self, _cells: u32) -> u32 {
+        0
+    }
+
+    fn hard_drop(&self, _cells: u32) -> u32 {
+        0
+    }
+
+    fn clone_box(&self) -> Box<dyn Scorer> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: u32, t_spin: TSpin) -> LineClear {
+        LineClear {
+            lines,
+            t_spin,
+            perfect_clear: false,
+            combo: 1,
+            combo_lines: lines,
+            back_to_back: false,
+            dropped: 0,
+            level_after: 1,
+        }
+    }
+
+    #[test]
+    fn guideline_values_scale_with_level() {
+        let scorer = GuidelineScorer;
+        assert_eq!(scorer.line_clear(&clear(1, TSpin::None), 1), 100);
+        assert_eq!(scorer.line_clear(&clear(2, TSpin::None), 1), 300);
+        assert_eq!(scorer.line_clear(&clear(3, TSpin::None), 1), 500);
+        assert_eq!(scorer.line_clear(&clear(4, TSpin::None), 1), 800);
+        assert_eq!(scorer.line_clear(&clear(0, TSpin::Mini), 1), 100);
+        assert_eq!(scorer.line_clear(&clear(1, TSpin::Mini), 1), 200);
+        assert_eq!(scorer.line_clear(&clear(0, TSpin::Full), 1), 400);
+        assert_eq!(scorer.line_clear(&clear(2, TSpin::Full), 1), 1200);
+        assert_eq!(scorer.line_clear(&clear(3, TSpin::Full), 1), 1600);
+        assert_eq!(scorer.line_clear(&clear(4, TSpin::None), 3), 2400);
+        assert_eq!(scorer.soft_drop(5), 5);
+        assert_eq!(scorer.hard_drop(5), 10);
+    }
+
+    #[test]
+    fn back_to_back_combo_and_perfect_clear_bonuses() {
+        let scorer = GuidelineScorer;
+        let tetris = clear(4, TSpin::None);
+        let b2b = LineClear { back_to_back: true, ..tetris };
+        assert_eq!(scorer.line_clear(&b2b, 1), 1200);
+        assert_eq!(scorer.line_clear(&LineClear { back_to_back: true, ..clear(2, TSpin::Full) }, 1), 1800);
+        assert_eq!(scorer.line_clear(&LineClear { combo: 3, ..clear(1, TSpin::None) }, 1), 200);
+        assert_eq!(scorer.line_clear(&LineClear { perfect_clear: true, ..clear(1, TSpin::None) }, 1), 900);
+        assert_eq!(scorer.line_clear(&LineClear { perfect_clear: true, ..tetris }, 1), 2800);
+        assert_eq!(scorer.line_clear(&LineClear { perfect_clear: true, ..b2b }, 2), 8800);
+    }
+
+    #[test]
+    fn difficult_clears_are_tetrises_and_line_clearing_spins() {
+        assert!(clear(4, TSpin::None).is_difficult());
+        assert!(clear(1, TSpin::Mini).is_difficult());
+        assert!(!clear(3, TSpin::None).is_difficult());
+        assert!(!clear(0, TSpin::Full).is_difficult());
+    }
+
+    #[test]
+    fn tgm_combo_drops_bravo_and_level_bonus() {
+        let scorer = TgmScorer;
+        let tetris = LineClear { level_after: 5, ..clear(4, TSpin::None) };
+        // A tetris alone is already a combo of 1 + 2 * 4 - 2 = 7
+        assert_eq!(scorer.line_clear(&tetris, 0), 4 * 7 + 3);
+        assert_eq!(scorer.line_clear(&LineClear { dropped: 10, ..tetris }, 0), 11 * 4 * 7 + 3);
+        assert_eq!(scorer.line_clear(&LineClear { perfect_clear: true, ..tetris }, 0), 4 * 7 * 4 + 3);
+        // A double straight after: combo 7 + 2 * 2 - 2 = 9
+        let double = LineClear { combo: 2, combo_lines: 6, level_after: 8, ..clear(2, TSpin::None) };
+        assert_eq!(scorer.line_clear(&double, 5), 2 * 2 * 9 + 4);
+        // Singles never build a combo
+        let single = LineClear { combo: 3, combo_lines: 3, level_after: 11, ..clear(1, TSpin::None) };
+        assert_eq!(scorer.line_clear(&single, 10), 3 + 6);
+        assert_eq!(scorer.soft_drop(5) + scorer.hard_drop(5), 0);
+    }
+}
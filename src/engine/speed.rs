@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use super::gravity::{self, FRAME_RATE, MAX_GRAVITY};
+
+/// Delays that can change with the level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Delays {
+    pub are: Duration,
+    pub line_clear_delay: Duration,
+    pub lock_delay: Duration,
+}
+
+/// How gravity and delays change with the level, as tables of steps. Each step applies
+/// from its level until the next one; an empty table falls back to the default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpeedCurve {
+    // Cells per frame; empty: the guideline formula
+    pub gravity: Vec<(u32, f32)>,
+    // Empty: the ruleset's fixed delays
+    pub delays: Vec<(u32, Delays)>,
+}
+
+impl SpeedCurve {
+    /// TGM2 Master: gravity ramps (with a dip at 200) to 20G by level 500, then the delays shrink.
+    pub fn master() -> Self {
+        // In 1/256ths of a cell per frame
+        const GRAVITY: [(u32, u32); 30] = [
+            (0, 4),
+            (30, 6),
+            (35, 8),
+            (40, 10),
+            (50, 12),
+            (60, 16),
+            (70, 32),
+            (80, 48),
+            (90, 64),
+            (100, 80),
+            (120, 96),
+            (140, 112),
+            (160, 128),
+            (170, 144),
+            (200, 4),
+            (220, 32),
+            (230, 64),
+            (233, 96),
+            (236, 128),
+            (239, 160),
+            (243, 192),
+            (247, 224),
+            (251, 256),
+            (300, 512),
+            (330, 768),
+            (360, 1024),
+            (400, 1280),
+            (420, 1024),
+            (450, 768),
+            (500, 5120),
+        ];
+        // (level, ARE, line clear, lock delay) in frames
+        const DELAYS: [(u32, u32, u32, u32); 6] = [
+            (0, 25, 40, 30),
+            (500, 25, 25, 30),
+            (600, 25, 16, 30),
+            (700, 16, 12, 30),
+            (800, 12, 6, 30),
+            (900, 12, 6, 17),
+        ];
+        Self {
+            gravity: GRAVITY.map(|(level, g)| (level, g as f32 / 256.0)).to_vec(),
+            delays: DELAYS
+                .map(|(level, are, line_clear, lock)| {
+                    let delays = Delays {
+                        are: frame_time(are),
+                        line_clear_delay: frame_time(line_clear),
+                        lock_delay: frame_time(lock),
+                    };
+                    (level, delays)
+                })
+                .to_vec(),
+        }
+    }
+
+    /// Cells per frame at `level`, capped at 20G.
+    pub fn gravity(&self, level: u32) -> f32 {
+        match step_at(&self.gravity, level) {
+            Some(g) => g.min(MAX_GRAVITY),
+            None => gravity::gravity(level),
+        }
+    }
+
+    /// The delays at `level`, if the curve sets them.
+    pub fn delays(&self, level: u32) -> Option<Delays> {
+        step_at(&self.delays, level)
+    }
+}
+
+/// The last step at or below `level`.
+fn step_at<T: Copy>(steps: &[(u32, T)], level: u32) -> Option<T> {
+    steps.iter().rev().find(|(from, _)| *from <= level).map(|(_, value)| *value)
+}
+
+fn frame_time(frames: u32) -> Duration {
+    Duration::from_secs_f32(frames as f32 / FRAME_RATE)
+}
@@ -0,0 +1,151 @@
+use super::board::Board;
+use super::game::ActivePiece;
+use super::piece::{KickTable, TetrominoType};
+use super::rotation::RotationKind;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TSpin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// A rotation that went through, as far as spin detection cares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Kick {
+    // Position in the kick table (0 = no offset)
+    pub index: usize,
+    // The SRS TST/fin kick, which always makes a full T-spin
+    pub upgrades: bool,
+}
+
+// Index of the SRS kick that always upgrades a mini to a full T-spin (the TST/fin kick)
+const UPGRADE_KICK: usize = 4;
+
+impl Kick {
+    /// Kick `index` of a rotation by `turns` quarter turns. Only quarter turns through the SRS
+    /// table upgrade; 180° and custom tables number their kicks differently.
+    pub fn new(piece: &ActivePiece, turns: usize, index: usize) -> Self {
+        let upgrades = index == UPGRADE_KICK
+            && turns % 2 == 1
+            && piece.rotation_system == RotationKind::Srs
+            && piece.piece_type.def().kicks == KickTable::Srs;
+        Self { index, upgrades }
+    }
+}
+
+/// 3-corner rule. `last_kick` is the kick the last move took if it was a rotation.
+pub fn classify(board: &Board, piece: &ActivePiece, last_kick: Option<Kick>) -> TSpin {
+    let Some(kick) = last_kick else { return TSpin::None };
+    if TetrominoType::of(piece.piece_type) != Some(TetrominoType::T) {
+        return TSpin::None;
+    }
+
+    // The T's centre is the block with three neighbours; it points away from the missing one.
+    // Working from the blocks keeps this independent of how a rotation system numbers its states.
+    let blocks = piece.blocks();
+    let scale = piece.scale;
+    let sides = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+    let Some(((cx, cy), (px, py))) = blocks.iter().find_map(|&(x, y)| {
+        let missing: Vec<_> = sides
+            .iter()
+            .copied()
+            .filter(|&(dx, dy)| !blocks.contains(&(x + dx * scale, y + dy * scale)))
+            .collect();
+        match missing[..] {
+            [(dx, dy)] => Some(((x, y), (-dx, -dy))),
+            _ => None,
+        }
+    }) else {
+        return TSpin::None;
+    };
+
+    // Walls and floor count as filled, as does a corner block-sized square with anything in it
+    let filled = |(dx, dy): (i32, i32)| {
+        (0..scale * scale).any(|i| !board.is_valid_position(cx + dx * scale + i % scale, cy + dy * scale + i / scale))
+    };
+    let corners = [(-1, 1), (1, 1), (1, -1), (-1, -1)];
+    if corners.iter().filter(|&&corner| filled(corner)).count() < 3 {
+        return TSpin::None;
+    }
+
+    // The two corners either side of the way the T points
+    let front = [(px - py, py + px), (px + py, py - px)];
+    if front.iter().all(|&corner| filled(corner)) || kick.upgrades {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::board::Cell;
+    use crate::engine::piece::PieceSet;
+
+    fn t_piece(x: i32, y: i32, rotation_state: usize) -> ActivePiece {
+        ActivePiece {
+            piece_type: PieceSet::tetromino().find("T").unwrap(),
+            x,
+            y,
+            rotation_state,
+            rotation_system: RotationKind::Srs,
+            scale: 1,
+        }
+    }
+
+    fn board(filled: &[(i32, i32)]) -> Board {
+        let mut board = Board::new(10, 20, 20);
+        for &(x, y) in filled {
+            board.set(x, y, Cell::Garbage);
+        }
+        board
+    }
+
+    fn kick(index: usize) -> Option<Kick> {
+        Some(Kick { index, upgrades: false })
+    }
+
+    #[test]
+    fn t_spin_double_slot_is_full() {
+        // Bottom row open at 4, next row open at 3-5, roofed over at 3
+        let mut filled: Vec<(i32, i32)> = (0..10).filter(|&x| x != 4).map(|x| (x, 0)).collect();
+        filled.extend((0..10).filter(|x| !(3..=5).contains(x)).map(|x| (x, 1)));
+        filled.push((3, 2));
+        let board = board(&filled);
+        let piece = t_piece(4, 1, 2);
+        assert_eq!(classify(&board, &piece, kick(0)), TSpin::Full);
+        // Sliding in without a rotation isn't a spin
+        assert_eq!(classify(&board, &piece, None), TSpin::None);
+    }
+
+    #[test]
+    fn one_front_corner_is_a_mini_unless_the_tst_kick_upgrades_it() {
+        // Pointing up with both back corners and one front corner filled
+        let board = board(&[(3, 0), (5, 0), (3, 2)]);
+        let piece = t_piece(4, 1, 0);
+        assert_eq!(classify(&board, &piece, kick(0)), TSpin::Mini);
+        assert_eq!(classify(&board, &piece, Some(Kick { index: 4, upgrades: true })), TSpin::Full);
+        // Index 4 of another table (the 180° one) stays a mini
+        assert_eq!(classify(&board, &piece, kick(4)), TSpin::Mini);
+    }
+
+    #[test]
+    fn only_srs_quarter_turns_upgrade() {
+        let piece = t_piece(4, 1, 0);
+        assert!(Kick::new(&piece, 1, UPGRADE_KICK).upgrades);
+        assert!(Kick::new(&piece, 3, UPGRADE_KICK).upgrades);
+        assert!(!Kick::new(&piece, 2, UPGRADE_KICK).upgrades);
+        assert!(!Kick::new(&piece, 1, UPGRADE_KICK - 1).upgrades);
+        let ars = ActivePiece { rotation_system: RotationKind::Ars, ..piece };
+        assert!(!Kick::new(&ars, 1, UPGRADE_KICK).upgrades);
+    }
+
+    #[test]
+    fn two_corners_are_not_a_spin() {
+        let board = board(&[(3, 0), (5, 0)]);
+        assert_eq!(classify(&board, &t_piece(4, 1, 0), kick(0)), TSpin::None);
+    }
+}
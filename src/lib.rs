@@ -0,0 +1,4 @@
+//! The Bevy-free rules of the game, shared by the app and by anything else
+//! (tools, bots, tests) that wants to run a game headless.
+
+pub mod engine;
@@ -1,17 +1,18 @@
 use bevy::prelude::*;
 
+mod cli;
 mod components;
 mod resources;
 mod systems;
 
-use resources::{Grid, GameScore, GameTimer};
-use systems::setup::{setup_camera, setup_grid};
-use systems::spawning::{spawn_tetromino, setup_next_piece};
+use resources::{BoardLayout, GameScore, PersonalBests, PlayerInput, WINDOW_HEIGHT, WINDOW_WIDTH};
+use systems::setup::{fit_board, setup_camera, setup_grid};
+use systems::spawning::{setup_game, sync_active_piece};
 use systems::rendering::render_blocks;
-use systems::movement::{handle_input, apply_gravity};
-use systems::locking::{lock_piece_system, PieceLocks};
-use systems::line_clearing::clear_lines;
-use systems::ui::{setup_ui, update_score, update_next_piece, update_hold_piece};
+use systems::movement::{auto_repeat, handle_input, AutoRepeat};
+use systems::locking::{fade_stack, lock_piece_system};
+use systems::game::{step_game, sync_hud, GameMessage};
+use systems::ui::{setup_ui, update_clock, update_score, update_next_piece, update_hold_piece};
 use systems::game_over::{game_over_setup, restart_game, cleanup_game_over};
 use systems::ghost::render_ghost;
 use systems::audio::{setup_audio, play_sound_system, SoundEvent};
@@ -24,31 +25,44 @@ pub enum GameState {
 }
 
 fn main() {
+    let (settings, bindings, handling) = cli::parse_args(std::env::args().skip(1));
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Bevy Tetris".into(),
-                resolution: (800, 600).into(),
+                resolution: (WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32).into(),
                 ..default()
             }),
             ..default()
         }))
         .init_state::<GameState>()
-        .init_resource::<Grid>()
+        .insert_resource(settings)
+        .insert_resource(bindings)
+        .insert_resource(handling)
+        .insert_resource(PersonalBests::load())
+        .init_resource::<BoardLayout>()
+        .init_resource::<AutoRepeat>()
         .init_resource::<GameScore>()
         .init_resource::<resources::HoldPiece>()
-        .insert_resource(GameTimer(Timer::from_seconds(0.5, TimerMode::Repeating)))
-        .add_message::<PieceLocks>()
+        .init_resource::<PlayerInput>()
+        .add_message::<GameMessage>()
         .add_message::<SoundEvent>()
-        .add_systems(Startup, (setup_camera, setup_grid, setup_next_piece, setup_ui, setup_audio))
+        .add_systems(Startup, (setup_camera, setup_grid, setup_game, setup_ui, setup_audio))
         .add_systems(Update, (
-            spawn_tetromino,
-            handle_input,
-            apply_gravity,
-            lock_piece_system,
-            clear_lines,
-            render_blocks,
+            (
+                auto_repeat,
+                handle_input,
+                step_game,
+                sync_hud,
+                lock_piece_system,
+                fade_stack,
+                sync_active_piece,
+                fit_board,
+                render_blocks,
+            ).chain(),
             update_score,
+            update_clock,
             update_next_piece,
             update_hold_piece,
             render_ghost,
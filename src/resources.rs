@@ -1,58 +1,160 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use bevy::prelude::*;
-use crate::components::TetrominoType;
+use crate::components::PieceType;
+use bevy_tetris::engine::{random_seed, Game, ModeKind, PieceSet, Ruleset, Score, DEFAULT_HEIGHT, DEFAULT_WIDTH};
 
-pub const GRID_WIDTH: i32 = 10;
-pub const GRID_HEIGHT: i32 = 20;
-pub const CELL_SIZE: f32 = 30.0;
 pub const WINDOW_WIDTH: f32 = 800.0;
 pub const WINDOW_HEIGHT: f32 = 600.0;
+// Kept clear on each side of the board for the hold and next panels
+pub const SIDE_PANEL_WIDTH: f32 = 250.0;
+// Gap between neighbouring block sprites
+const BLOCK_GAP: f32 = 2.0;
+
+/// Where the board is drawn: cells as large as fit between the side panels, board centred.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct BoardLayout {
+    pub cell_size: f32,
+    // Visible cells across and up
+    pub width: i32,
+    pub height: i32,
+}
+
+impl BoardLayout {
+    pub fn fit(width: i32, height: i32, window: Vec2) -> Self {
+        let available = Vec2::new((window.x - 2.0 * SIDE_PANEL_WIDTH).max(0.0), window.y);
+        Self {
+            cell_size: (available.x / width as f32).min(available.y / height as f32).floor().max(1.0),
+            width,
+            height,
+        }
+    }
+
+    /// The whole visible board in pixels.
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * self.cell_size
+    }
+
+    /// World position of the centre of cell (x, y).
+    pub fn cell_center(&self, x: i32, y: i32) -> Vec2 {
+        -self.size() / 2.0 + (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size
+    }
+
+    /// Sprite size for one block.
+    pub fn block_size(&self) -> Vec2 {
+        Vec2::splat(self.cell_size - BLOCK_GAP)
+    }
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self {
+        Self::fit(DEFAULT_WIDTH, DEFAULT_HEIGHT, Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT))
+    }
+}
+
+/// Chosen before a game starts and reused on restart.
+#[derive(Resource, Default, Clone)]
+pub struct GameSettings {
+    pub ruleset: Ruleset,
+    // Fixed seed for every game, or None for a fresh one each time
+    pub seed: Option<u64>,
+}
+
+impl GameSettings {
+    pub fn pick_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(random_seed)
+    }
+}
 
-// Calculate board dimensions
-pub const BOARD_WIDTH_PX: f32 = GRID_WIDTH as f32 * CELL_SIZE;
-pub const BOARD_HEIGHT_PX: f32 = GRID_HEIGHT as f32 * CELL_SIZE;
+/// Seed of the game in progress; share it to replay the same piece sequence.
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct GameSeed(pub u64);
 
-// Center the board
-pub const BOARD_ORIGIN_X: f32 = -BOARD_WIDTH_PX / 2.0;
-pub const BOARD_ORIGIN_Y: f32 = -BOARD_HEIGHT_PX / 2.0;
+/// The running game. Systems feed it input and mirror its state into the world.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct TetrisGame(pub Game);
 
-#[derive(Resource)]
-pub struct Grid {
-    // 2D array representing the grid. 
-    // Stores the color of the block if occupied, or None if empty.
-    pub cells: [[Option<Color>; GRID_WIDTH as usize]; GRID_HEIGHT as usize],
+impl TetrisGame {
+    pub fn new(settings: &GameSettings, seed: GameSeed) -> Self {
+        Self(Game::new(settings.ruleset.clone(), seed.0))
+    }
+}
+
+/// Which keys drive which engine input. Any key in a list triggers it.
+#[derive(Resource, Clone)]
+pub struct KeyBindings {
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub soft_drop: Vec<KeyCode>,
+    pub hard_drop: Vec<KeyCode>,
+    // Unbound unless set on the command line
+    pub sonic_drop: Vec<KeyCode>,
+    pub rotate_cw: Vec<KeyCode>,
+    pub rotate_ccw: Vec<KeyCode>,
+    pub rotate_180: Vec<KeyCode>,
+    pub hold: Vec<KeyCode>,
 }
 
-impl Default for Grid {
+impl Default for KeyBindings {
     fn default() -> Self {
         Self {
-            cells: [[None; GRID_WIDTH as usize]; GRID_HEIGHT as usize],
+            left: vec![KeyCode::ArrowLeft],
+            right: vec![KeyCode::ArrowRight],
+            soft_drop: vec![KeyCode::ArrowDown],
+            hard_drop: vec![KeyCode::Space],
+            sonic_drop: Vec::new(),
+            rotate_cw: vec![KeyCode::ArrowUp, KeyCode::KeyX],
+            rotate_ccw: vec![KeyCode::KeyZ],
+            rotate_180: vec![KeyCode::KeyA],
+            hold: vec![KeyCode::KeyC, KeyCode::ShiftLeft, KeyCode::ShiftRight],
         }
     }
 }
 
-#[derive(Resource)]
-pub struct ActivePiece {
-    pub piece_type: TetrominoType,
-    pub position: IVec2, // Grid coordinates
-    pub rotation_state: usize, // 0, 1, 2, 3
+/// Auto-repeat timings for sideways movement and soft drop.
+#[derive(Resource, Clone)]
+pub struct Handling {
+    // Delayed auto shift: how long a direction is held before it repeats
+    pub das: Duration,
+    // Auto repeat rate: time between repeats once charged; zero shifts straight to the wall
+    pub arr: Duration,
+    // Soft drop speed as a multiple of gravity; infinite drops to the floor
+    pub soft_drop_factor: f32,
 }
 
-#[derive(Resource)]
-pub struct NextPiece {
-    pub piece_type: TetrominoType,
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: Duration::from_millis(167),
+            arr: Duration::from_millis(33),
+            soft_drop_factor: 20.0,
+        }
+    }
 }
 
+/// Buttons pressed this frame, collected by `handle_input` for `step_game`.
 #[derive(Resource, Default)]
+pub struct PlayerInput(pub bevy_tetris::engine::Inputs);
+
+#[derive(Resource, PartialEq)]
+pub struct NextQueue {
+    // Soonest first
+    pub pieces: Vec<PieceType>,
+}
+
+#[derive(Resource, Default, PartialEq)]
 pub struct HoldPiece {
-    pub piece_type: Option<TetrominoType>,
+    pub piece_type: Option<PieceType>,
     pub can_hold: bool,
 }
 
-#[derive(Resource)]
+#[derive(Resource, PartialEq)]
 pub struct GameScore {
     pub score: u32,
     pub level: u32,
     pub lines_cleared: u32,
+    pub pieces: u32,
 }
 
 impl Default for GameScore {
@@ -61,9 +163,103 @@ impl Default for GameScore {
             score: 0,
             level: 1,
             lines_cleared: 0,
+            pieces: 0,
+        }
+    }
+}
+
+impl From<&Score> for GameScore {
+    fn from(score: &Score) -> Self {
+        Self {
+            score: score.score,
+            level: score.level,
+            lines_cleared: score.lines_cleared,
+            pieces: score.pieces,
         }
     }
 }
 
-#[derive(Resource)]
-pub struct GameTimer(pub Timer);
+/// A finished timed run.
+#[derive(Clone)]
+pub struct Record {
+    pub time: Duration,
+    pub splits: Vec<Duration>,
+}
+
+/// Best Sprint times per line goal and board variant. Saved to `FILE` where there is a filesystem;
+/// elsewhere (the web build) they last for the session.
+#[derive(Resource, Default)]
+pub struct PersonalBests {
+    records: HashMap<String, Record>,
+}
+
+impl PersonalBests {
+    const FILE: &'static str = "personal_bests.txt";
+
+    /// Which record a game counts towards, if its mode keeps one. Big mode, other piece sets
+    /// and other board sizes keep records of their own, e.g. `sprint40-big-pentomino-6x20`.
+    pub fn key(ruleset: &Ruleset) -> Option<String> {
+        let mode = &ruleset.mode;
+        let (ModeKind::Sprint, Some(goal)) = (mode.kind, mode.line_goal) else { return None };
+        let mut key = format!("sprint{}", goal);
+        if mode.big {
+            key.push_str("-big");
+        }
+        if ruleset.pieces != PieceSet::tetromino() {
+            // Keys are whitespace-separated in the file
+            key.push('-');
+            key.extend(ruleset.pieces.name.chars().map(|c| if c.is_whitespace() { '_' } else { c }));
+        }
+        if (ruleset.width, ruleset.height) != (DEFAULT_WIDTH, DEFAULT_HEIGHT) {
+            key.push_str(&format!("-{}x{}", ruleset.width, ruleset.height));
+        }
+        Some(key)
+    }
+
+    /// One record per line: `<key> <total ms> <split ms,split ms,...>`. Unreadable lines are skipped.
+    pub fn load() -> Self {
+        let mut bests = Self::default();
+        let Ok(contents) = std::fs::read_to_string(Self::FILE) else { return bests };
+        for line in contents.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(key), Some(Ok(time))) = (fields.next(), fields.next().map(str::parse)) else { continue };
+            let splits = fields
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|ms| ms.parse().ok())
+                .map(Duration::from_millis)
+                .collect();
+            bests.records.insert(key.to_string(), Record { time: Duration::from_millis(time), splits });
+        }
+        bests
+    }
+
+    fn save(&self) {
+        let contents: String = self
+            .records
+            .iter()
+            .map(|(key, record)| {
+                let splits: Vec<String> = record.splits.iter().map(|split| split.as_millis().to_string()).collect();
+                format!("{} {} {}\n", key, record.time.as_millis(), splits.join(","))
+            })
+            .collect();
+        if let Err(err) = std::fs::write(Self::FILE, contents) {
+            eprintln!("Couldn't save personal bests: {}", err);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Record> {
+        self.records.get(key)
+    }
+
+    /// Keeps `record` if it beats the current best, returning true if it did.
+    pub fn submit(&mut self, key: String, record: Record) -> bool {
+        if self.records.get(&key).is_some_and(|best| best.time <= record.time) {
+            return false;
+        }
+        self.records.insert(key, record);
+        self.save();
+        true
+    }
+}
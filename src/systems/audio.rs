@@ -33,20 +33,13 @@ pub fn setup_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
         game_over_sfx: asset_server.load("audio/gameover.ogg"),
     };
     
-    commands.insert_resource(audio);
-    
     // Play BGM (looped)
-    // commands.spawn(AudioBundle {
-    //     source: audio.bgm.clone(),
-    //     settings: PlaybackSettings::LOOP,
-    // });
-    // Bevy 0.17 Audio API might differ slightly.
-    // Using spawn((AudioPlayer(source), PlaybackSettings::LOOP))
-    
     commands.spawn((
-        AudioPlayer::<AudioSource>(asset_server.load("audio/bgm.ogg")),
+        AudioPlayer(audio.bgm.clone()),
         PlaybackSettings::LOOP,
     ));
+
+    commands.insert_resource(audio);
 }
 
 pub fn play_sound_system(
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_tetris::engine::GameEvent;
+use crate::resources::{GameScore, HoldPiece, NextQueue, PlayerInput, TetrisGame};
+use crate::systems::audio::SoundEvent;
+use crate::GameState;
+
+/// Engine events re-broadcast to the rest of the app.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct GameMessage(pub GameEvent);
+
+pub fn step_game(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    mut game: ResMut<TetrisGame>,
+    mut game_events: MessageWriter<GameMessage>,
+    mut sound_events: MessageWriter<SoundEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in game.step(input.0, time.delta()) {
+        match event {
+            GameEvent::Moved | GameEvent::SonicDropped(_) => { sound_events.write(SoundEvent::Move); }
+            GameEvent::Rotated => { sound_events.write(SoundEvent::Rotate); }
+            GameEvent::Locked { .. } => { sound_events.write(SoundEvent::Lock); }
+            GameEvent::LinesCleared(_) => { sound_events.write(SoundEvent::Clear); }
+            GameEvent::GameOver(_) => next_state.set(GameState::GameOver),
+            _ => {}
+        }
+        game_events.write(GameMessage(event));
+    }
+}
+
+/// Mirrors the engine's score, next queue and hold piece into the resources the UI watches.
+pub fn sync_hud(
+    game: Res<TetrisGame>,
+    mut score: ResMut<GameScore>,
+    mut next_queue: ResMut<NextQueue>,
+    mut hold_piece: ResMut<HoldPiece>,
+) {
+    score.set_if_neq(GameScore::from(game.score()));
+    next_queue.set_if_neq(NextQueue {
+        pieces: game.next_queue().iter().copied().collect(),
+    });
+    hold_piece.set_if_neq(HoldPiece {
+        piece_type: game.hold(),
+        can_hold: game.can_hold(),
+    });
+}
@@ -1,50 +1,155 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use crate::{GameState, resources::{Grid, GameScore, GRID_HEIGHT, GRID_WIDTH}, components::Block};
+use bevy_tetris::engine::{Ending, Leveling};
+use crate::{GameState, resources::{GameSeed, GameSettings, PersonalBests, Record, TetrisGame}, components::Block};
+use crate::systems::ui::{format_delta, format_time};
 
 #[derive(Component)]
 pub struct GameOverUI;
 
 pub fn game_over_setup(
     mut commands: Commands,
+    seed: Res<GameSeed>,
+    game: Res<TetrisGame>,
+    mut bests: ResMut<PersonalBests>,
     mut sound_events: MessageWriter<crate::systems::audio::SoundEvent>,
 ) {
-    sound_events.write(crate::systems::audio::SoundEvent::GameOver);
+    if !game.ending().is_some_and(|ending| ending.is_win()) {
+        sound_events.write(crate::systems::audio::SoundEvent::GameOver);
+    }
+    let results = results(&game, &mut bests);
     
     commands.spawn((
-        Text::new("GAME OVER\nPress R to Restart"),
+        Text::new(format!("{}\nPress R to Restart", title(&game))),
         TextFont {
             font_size: 60.0,
             ..default()
         },
-        TextColor(Color::WHITE),
+        TextColor(title_color(&game)),
         Node {
             position_type: PositionType::Absolute,
-            top: Val::Percent(40.0),
+            top: Val::Percent(10.0),
             left: Val::Percent(20.0),
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
             ..default()
         },
         GameOverUI,
-    ));
+    )).with_children(|parent| {
+        parent.spawn((
+            TextSpan::new(results),
+            TextFont {
+                font_size: 30.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+        parent.spawn((
+            TextSpan::new(format!("\nSeed: {}\nPress S to replay this seed", seed.0)),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+/// Final stats, plus split times and the personal-best comparison for a finished timed run
+/// (which is also recorded here).
+fn results(game: &TetrisGame, bests: &mut PersonalBests) -> String {
+    let score = game.score();
+    let seconds = game.elapsed().as_secs_f32();
+    let pps = if seconds > 0.0 { score.pieces as f32 / seconds } else { 0.0 };
+    let mut text = format!(
+        "\nScore: {}\nLevel: {}\nLines: {}\nPieces: {} ({:.2} PPS)\nTime: {}",
+        score.score,
+        score.level,
+        score.lines_cleared,
+        score.pieces,
+        pps,
+        format_time(game.elapsed()),
+    );
+    if let (Some(cleared), Some(dig)) = (game.garbage_cleared(), game.ruleset().mode.dig) {
+        text += &format!("\nGarbage: {} / {}", cleared, dig.total);
+    }
+    if let Some(grade) = game.grade() {
+        text += &format!("\nGrade: {}", grade.name());
+    }
+    if matches!(game.ruleset().mode.leveling, Leveling::Sections { .. }) {
+        let mut previous = Duration::ZERO;
+        for (i, split) in game.splits().iter().enumerate() {
+            let level = (i as u32 + 1) * Leveling::SECTION;
+            text += &format!("\n  Level {}: {} ({})", level, format_time(*split), format_time(*split - previous));
+            previous = *split;
+        }
+    }
+
+    let Some(key) = PersonalBests::key(game.ruleset()) else { return text };
+    if game.ending() != Some(Ending::Cleared) {
+        return text;
+    }
+    let previous = bests.get(&key).cloned();
+    let record = Record {
+        time: game.elapsed(),
+        splits: game.splits().to_vec(),
+    };
+    if let Some(previous) = &previous {
+        text += &format!(" ({} vs PB {})", format_delta(record.time, previous.time), format_time(previous.time));
+    }
+    if let Some(split_lines) = game.ruleset().mode.split_lines {
+        for (i, split) in record.splits.iter().enumerate() {
+            text += &format!("\n  {} lines: {}", (i as u32 + 1) * split_lines, format_time(*split));
+            if let Some(pb_split) = previous.as_ref().and_then(|pb| pb.splits.get(i)) {
+                text += &format!(" ({})", format_delta(*split, *pb_split));
+            }
+        }
+    }
+    if bests.submit(key, record) {
+        text += "\nNEW PERSONAL BEST!";
+    }
+    text
+}
+
+/// How the game ended (the top-out reason, or a win) in place of a generic "GAME OVER".
+fn title(game: &TetrisGame) -> String {
+    match game.ending() {
+        Some(ending) => ending.label().to_uppercase(),
+        None => "GAME OVER".to_string(),
+    }
+}
+
+fn title_color(game: &TetrisGame) -> Color {
+    if game.ending().is_some_and(|ending| ending.is_win()) {
+        Color::srgb(1.0, 0.85, 0.2) // Gold
+    } else {
+        Color::WHITE
+    }
 }
 
 pub fn restart_game(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut grid: ResMut<Grid>,
-    mut score: ResMut<GameScore>,
+    mut game: ResMut<TetrisGame>,
+    mut seed: ResMut<GameSeed>,
+    settings: Res<GameSettings>,
     blocks: Query<Entity, With<Block>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyR) {
-        // Reset Grid
-        grid.cells = [[None; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-        
-        // Reset Score
-        score.score = 0;
-        score.level = 1;
-        score.lines_cleared = 0;
+    // R picks a new seed (unless one was fixed on the command line), S replays the last one
+    let new_seed = if keyboard_input.just_pressed(KeyCode::KeyR) {
+        Some(GameSeed(settings.pick_seed()))
+    } else if keyboard_input.just_pressed(KeyCode::KeyS) {
+        Some(*seed)
+    } else {
+        None
+    };
+
+    if let Some(new_seed) = new_seed {
+        // Start a fresh game (board, score, hold and next all reset)
+        *seed = new_seed;
+        *game = TetrisGame::new(&settings, new_seed);
         
         // Despawn all blocks
         for entity in blocks.iter() {
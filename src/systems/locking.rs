@@ -1,42 +1,87 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use crate::components::{Active, GridPosition};
-use crate::resources::{ActivePiece, Grid};
+use crate::components::{Active, Block, GridPosition, PieceColor};
+use bevy_tetris::engine::{GameEvent, Phase, StackVisibility};
+use crate::resources::{BoardLayout, TetrisGame};
+use crate::systems::game::GameMessage;
+
+// Blocks of the locked stack, as opposed to the falling piece
+type StackFilter = (With<Block>, Without<Active>);
 
-#[derive(Message)]
-pub struct PieceLocks;
+// Locked blocks are slightly darker than the falling piece
+const LOCKED_ALPHA: f32 = 0.8;
 
+/// Rebuilds the locked-stack sprites from the engine board whenever a piece locks,
+/// cleared rows collapse or garbage rises. How visible each block is falls to `stack_alpha`.
 pub fn lock_piece_system(
     mut commands: Commands,
-    mut events: MessageReader<PieceLocks>,
-    mut grid: ResMut<Grid>,
-    active_piece: Option<ResMut<ActivePiece>>,
-    mut query: Query<(Entity, &GridPosition, &mut Sprite), With<Active>>,
-    mut hold_piece: ResMut<crate::resources::HoldPiece>,
-    mut sound_events: MessageWriter<crate::systems::audio::SoundEvent>,
+    mut events: MessageReader<GameMessage>,
+    game: Res<TetrisGame>,
+    layout: Res<BoardLayout>,
+    query: Query<Entity, StackFilter>,
 ) {
-    for _ in events.read() {
-        // Remove ActivePiece resource
-        if active_piece.is_none() {
-            continue; 
+    // Count rather than `any`, which would stop early and leave the rest for next frame
+    let changes = events
+        .read()
+        .filter(|GameMessage(event)| matches!(
+            event,
+            GameEvent::Locked { .. } | GameEvent::LinesCollapsed | GameEvent::GarbageRaised(_)
+        ))
+        .count();
+    if changes == 0 {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let board = game.board();
+    // Only the visible rows; anything in the buffer stays hidden
+    for y in 0..board.visible_height() {
+        for x in 0..board.width() {
+            let Some(cell) = board.get(x, y) else { continue };
+            commands.spawn((
+                Block,
+                GridPosition { x, y },
+                Sprite {
+                    color: cell.get_color().with_alpha(stack_alpha(&game, x, y)),
+                    custom_size: Some(layout.block_size()),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, 0.0),
+            ));
         }
-        commands.remove_resource::<ActivePiece>();
-        
-        // Reset hold status
-        hold_piece.can_hold = true;
-        
-        sound_events.write(crate::systems::audio::SoundEvent::Lock);
-
-        // Move blocks to grid
-        for (entity, pos, mut sprite) in query.iter_mut() {
-            commands.entity(entity).remove::<Active>();
-            
-            // Update grid
-            if pos.y >= 0 && pos.y < crate::resources::GRID_HEIGHT && pos.x >= 0 && pos.x < crate::resources::GRID_WIDTH {
-                grid.cells[pos.y as usize][pos.x as usize] = Some(sprite.color);
-            }
-            
-            // Visual feedback?
-            sprite.color = sprite.color.with_alpha(0.8); // Slightly darken?
+    }
+}
+
+/// Applies the mode's stack visibility each frame, and fades cleared rows out over the line-clear delay.
+pub fn fade_stack(
+    game: Res<TetrisGame>,
+    mut query: Query<(&GridPosition, &mut Sprite), StackFilter>,
+) {
+    for (pos, mut sprite) in query.iter_mut() {
+        let alpha = stack_alpha(&game, pos.x, pos.y);
+        if sprite.color.alpha() != alpha {
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
+
+/// Alpha for the locked block at (x, y). Hidden stacks are revealed once the game is over.
+fn stack_alpha(game: &TetrisGame, x: i32, y: i32) -> f32 {
+    let visibility = if game.is_over() { StackVisibility::Visible } else { game.ruleset().mode.stack };
+    let age = game.board().filled_at(x, y).map_or(Duration::ZERO, |at| game.elapsed().saturating_sub(at));
+    let mut alpha = LOCKED_ALPHA * visibility.opacity(age);
+
+    if let Phase::LineClear { remaining } = game.phase()
+        && game.clearing_rows().contains(&y)
+    {
+        let delay = game.delays().line_clear_delay;
+        if !delay.is_zero() {
+            alpha *= remaining.as_secs_f32() / delay.as_secs_f32();
         }
     }
+    alpha
 }
@@ -1,11 +1,10 @@
 pub mod setup;
 pub mod spawning;
 pub mod movement;
-pub mod collision;
 pub mod locking;
-pub mod line_clearing;
 pub mod rendering;
 pub mod ui;
 pub mod game_over;
 pub mod ghost;
 pub mod audio;
+pub mod game;
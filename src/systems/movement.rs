@@ -1,192 +1,131 @@
-use bevy::prelude::*;
-use crate::components::{Active, GridPosition};
-use crate::resources::{ActivePiece, GameTimer, Grid};
-use crate::systems::collision::is_valid_position;
+use std::time::Duration;
 
-pub fn handle_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    active_piece: Option<ResMut<ActivePiece>>,
-    grid: Res<Grid>,
-    mut query: Query<(Entity, &mut GridPosition), With<Active>>,
-    mut hold_piece: ResMut<crate::resources::HoldPiece>,
-    mut commands: Commands,
-    mut sound_events: MessageWriter<crate::systems::audio::SoundEvent>,
-) {
-    let Some(mut piece) = active_piece else { return };
+use bevy::prelude::*;
+use bevy_tetris::engine::gravity::frames;
+use bevy_tetris::engine::{HeldButtons, Inputs};
+use crate::resources::{GameScore, Handling, KeyBindings, PlayerInput, TetrisGame};
 
-    // Hold (C or Shift)
-    if keyboard_input.just_pressed(KeyCode::KeyC) || keyboard_input.just_pressed(KeyCode::ShiftLeft) || keyboard_input.just_pressed(KeyCode::ShiftRight) {
-        if hold_piece.can_hold {
-            // ... (existing hold logic)
-            // Despawn current active blocks
-            for (entity, _) in query.iter() {
-                commands.entity(entity).despawn();
-            }
-            
-            let current_type = piece.piece_type;
-            
-            // Swap or spawn
-            if let Some(held_type) = hold_piece.piece_type {
-                // Swap
-                hold_piece.piece_type = Some(current_type);
-                spawn_piece(&mut commands, held_type);
-            } else {
-                // First hold
-                hold_piece.piece_type = Some(current_type);
-                commands.remove_resource::<ActivePiece>();
-            }
-            
-            hold_piece.can_hold = false;
-            return; 
-        }
-    }
+/// Held-key state for DAS/ARR, plus the cells it produced this frame.
+#[derive(Resource, Default)]
+pub struct AutoRepeat {
+    // -1 left, 1 right, 0 none
+    direction: i32,
+    charge: Duration,
+    repeat: Duration,
+    // Fraction of a cell soft drop has built up towards the next one
+    soft_drop_progress: f32,
+    pub shift: i32,
+    pub soft_drop: u32,
+}
 
-    let mut delta = IVec2::ZERO;
-    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
-        delta.x -= 1;
-    }
-    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
-        delta.x += 1;
-    }
-    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
-        delta.y -= 1;
+impl AutoRepeat {
+    fn start(&mut self, direction: i32) {
+        // The first press always moves one cell, then DAS starts charging
+        self.direction = direction;
+        self.charge = Duration::ZERO;
+        self.repeat = Duration::ZERO;
+        self.shift += direction;
     }
 
-    if delta != IVec2::ZERO {
-        // Check if all current blocks can move by delta.
-        let mut can_move = true;
-        for (_, pos) in query.iter() {
-            if !is_valid_position(&grid, pos.x + delta.x, pos.y + delta.y) {
-                can_move = false;
-                break;
-            }
+    fn charge(&mut self, dt: Duration, handling: &Handling, board_width: i32) {
+        let before = self.charge;
+        self.charge += dt;
+        if self.charge < handling.das {
+            return;
         }
-
-        if can_move {
-            piece.position += delta;
-            for (_, mut pos) in query.iter_mut() {
-                pos.x += delta.x;
-                pos.y += delta.y;
-            }
-            sound_events.write(crate::systems::audio::SoundEvent::Move);
+        if handling.arr.is_zero() {
+            self.shift = self.direction * board_width;
+            return;
         }
-    }
-    
-    // Rotation (Up Arrow)
-    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
-        let old_rot = piece.rotation_state;
-        let new_rot = (old_rot + 1) % 4;
-        
-        let new_offsets = piece.piece_type.get_offsets_for_rotation(new_rot);
-        let kicks = piece.piece_type.get_srs_kicks(old_rot, new_rot);
-        
-        let mut successful_kick = None;
-        let mut final_new_positions = Vec::new();
-        
-        // Try each kick
-        for kick in kicks {
-            let test_pivot = piece.position + IVec2::new(kick.0, kick.1);
-            let mut kick_valid = true;
-            let mut kick_positions = Vec::new();
-            
-            for (x, y) in new_offsets.iter() {
-                let pos_x = test_pivot.x + x;
-                let pos_y = test_pivot.y + y;
-                
-                if !is_valid_position(&grid, pos_x, pos_y) {
-                    kick_valid = false;
-                    break;
-                }
-                kick_positions.push(GridPosition { x: pos_x, y: pos_y });
-            }
-            
-            if kick_valid {
-                successful_kick = Some(test_pivot);
-                final_new_positions = kick_positions;
-                break;
-            }
+        // DAS just charged: shift once right away, then every ARR
+        if before < handling.das {
+            self.shift += self.direction;
         }
-        
-        if let Some(new_pivot) = successful_kick {
-            // Apply rotation
-            piece.position = new_pivot;
-            piece.rotation_state = new_rot;
-            
-            let mut i = 0;
-            for (_, mut pos) in query.iter_mut() {
-                if i < final_new_positions.len() {
-                    *pos = final_new_positions[i];
-                    i += 1;
-                }
-            }
-            sound_events.write(crate::systems::audio::SoundEvent::Rotate);
+        self.repeat += self.charge - handling.das.max(before);
+        while self.repeat >= handling.arr {
+            self.repeat -= handling.arr;
+            self.shift += self.direction;
         }
     }
 }
 
-pub fn apply_gravity(
+/// Turns held keys into repeated shifts and soft drops, ahead of `handle_input`.
+pub fn auto_repeat(
     time: Res<Time>,
-    mut timer: ResMut<GameTimer>,
-    active_piece: Option<ResMut<ActivePiece>>,
-    grid: Res<Grid>,
-    mut query: Query<&mut GridPosition, With<Active>>,
-    mut lock_events: MessageWriter<crate::systems::locking::PieceLocks>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    handling: Res<Handling>,
+    score: Res<GameScore>,
+    game: Res<TetrisGame>,
+    mut repeat: ResMut<AutoRepeat>,
 ) {
-    if !timer.0.tick(time.delta()).just_finished() {
-        return;
-    }
-    
-    let Some(mut piece) = active_piece else { return };
-    
-    let delta = IVec2::new(0, -1);
-    
-    let mut can_move = true;
-    for pos in query.iter() {
-        if !is_valid_position(&grid, pos.x + delta.x, pos.y + delta.y) {
-            can_move = false;
-            break;
+    let pressed = |keys: &[KeyCode]| keyboard_input.any_just_pressed(keys.iter().copied());
+    let held = |keys: &[KeyCode]| keyboard_input.any_pressed(keys.iter().copied());
+    let dt = time.delta();
+    repeat.shift = 0;
+    repeat.soft_drop = 0;
+
+    // Sideways: the most recently pressed direction wins
+    if pressed(&bindings.left) {
+        repeat.start(-1);
+    } else if pressed(&bindings.right) {
+        repeat.start(1);
+    } else {
+        let (current, other) = match repeat.direction {
+            -1 => (&bindings.left, &bindings.right),
+            _ => (&bindings.right, &bindings.left),
+        };
+        if repeat.direction != 0 && held(current) {
+            repeat.charge(dt, &handling, game.board().width());
+        } else if repeat.direction != 0 && held(other) {
+            // Released the active direction while the other is still down
+            let direction = -repeat.direction;
+            repeat.start(direction);
+        } else {
+            repeat.direction = 0;
         }
     }
 
-    if can_move {
-        piece.position += delta;
-        for mut pos in query.iter_mut() {
-            pos.x += delta.x;
-            pos.y += delta.y;
+    // Soft drop: one cell on press, then gravity * soft_drop_factor while held
+    if pressed(&bindings.soft_drop) {
+        repeat.soft_drop = 1;
+        repeat.soft_drop_progress = 0.0;
+    } else if held(&bindings.soft_drop) {
+        let rate = game.ruleset().speed.gravity(score.level) * handling.soft_drop_factor;
+        if rate.is_infinite() {
+            // The engine stops at the floor, buffer rows and all
+            repeat.soft_drop = u32::MAX;
+        } else {
+            repeat.soft_drop_progress += rate * frames(dt);
+            let cells = repeat.soft_drop_progress.floor();
+            repeat.soft_drop_progress -= cells;
+            repeat.soft_drop = cells as u32;
         }
-    } else {
-        lock_events.write(crate::systems::locking::PieceLocks);
     }
 }
 
-fn spawn_piece(commands: &mut Commands, piece_type: crate::components::TetrominoType) {
-    use crate::resources::{CELL_SIZE, GRID_WIDTH, GRID_HEIGHT, ActivePiece};
-    use crate::components::{Block, Active, GridPosition};
-    
-    let spawn_position = IVec2::new(GRID_WIDTH / 2, GRID_HEIGHT - 2);
-    let offsets = piece_type.get_offsets_for_rotation(0);
-    let color = piece_type.get_color();
-    
-    for (x, y) in offsets.iter() {
-        commands.spawn((
-            Block,
-            Active,
-            GridPosition {
-                x: spawn_position.x + x,
-                y: spawn_position.y + y,
-            },
-            Sprite {
-                color,
-                custom_size: Some(Vec2::new(CELL_SIZE - 2.0, CELL_SIZE - 2.0)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 0.0, 0.0),
-        ));
-    }
-    
-    commands.insert_resource(ActivePiece {
-        piece_type,
-        position: spawn_position,
-        rotation_state: 0,
-    });
+pub fn handle_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    repeat: Res<AutoRepeat>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let pressed = |keys: &[KeyCode]| keyboard_input.any_just_pressed(keys.iter().copied());
+    let held = |keys: &[KeyCode]| keyboard_input.any_pressed(keys.iter().copied());
+    input.0 = Inputs {
+        shift: repeat.shift,
+        soft_drop: repeat.soft_drop,
+        hard_drop: pressed(&bindings.hard_drop),
+        sonic_drop: pressed(&bindings.sonic_drop),
+        rotate_cw: pressed(&bindings.rotate_cw),
+        rotate_ccw: pressed(&bindings.rotate_ccw),
+        rotate_180: pressed(&bindings.rotate_180),
+        hold: pressed(&bindings.hold),
+        held: HeldButtons {
+            rotate_cw: held(&bindings.rotate_cw),
+            rotate_ccw: held(&bindings.rotate_ccw),
+            rotate_180: held(&bindings.rotate_180),
+            hold: held(&bindings.hold),
+        },
+    };
 }
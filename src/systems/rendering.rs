@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 use crate::components::GridPosition;
-use crate::resources::{BOARD_ORIGIN_X, BOARD_ORIGIN_Y, CELL_SIZE};
+use crate::resources::BoardLayout;
 
 pub fn render_blocks(
-    mut query: Query<(&GridPosition, &mut Transform)>,
+    layout: Res<BoardLayout>,
+    mut query: Query<(&GridPosition, &mut Transform, &mut Visibility, &mut Sprite)>,
 ) {
-    for (pos, mut transform) in query.iter_mut() {
-        transform.translation.x = BOARD_ORIGIN_X + pos.x as f32 * CELL_SIZE + CELL_SIZE / 2.0;
-        transform.translation.y = BOARD_ORIGIN_Y + pos.y as f32 * CELL_SIZE + CELL_SIZE / 2.0;
+    for (pos, mut transform, mut visibility, mut sprite) in query.iter_mut() {
+        // Blocks in the hidden buffer rows aren't drawn
+        visibility.set_if_neq(if pos.y < layout.height { Visibility::Inherited } else { Visibility::Hidden });
+        let center = layout.cell_center(pos.x, pos.y);
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+        if sprite.custom_size != Some(layout.block_size()) {
+            sprite.custom_size = Some(layout.block_size());
+        }
     }
 }
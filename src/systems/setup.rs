@@ -1,21 +1,39 @@
 use bevy::prelude::*;
-use crate::resources::{GRID_WIDTH, GRID_HEIGHT};
+use crate::resources::{BoardLayout, TetrisGame};
+
+#[derive(Component)]
+pub struct BoardBackground;
 
 pub fn setup_camera(mut commands: Commands) {
     commands.spawn(Camera2d);
 }
 
-pub fn setup_grid(mut commands: Commands) {
+pub fn setup_grid(mut commands: Commands, layout: Res<BoardLayout>) {
     // Board background
     commands.spawn((
         Sprite {
             color: Color::srgb(0.1, 0.1, 0.1),
-            custom_size: Some(Vec2::new(
-                GRID_WIDTH as f32 * crate::resources::CELL_SIZE,
-                GRID_HEIGHT as f32 * crate::resources::CELL_SIZE,
-            )),
+            custom_size: Some(layout.size()),
             ..default()
         },
         Transform::from_xyz(0.0, 0.0, -1.0), // Behind blocks
+        BoardBackground,
     ));
 }
+
+/// Sizes cells to fit the board into the window, following window resizes.
+pub fn fit_board(
+    windows: Query<&Window>,
+    game: Res<TetrisGame>,
+    mut layout: ResMut<BoardLayout>,
+    mut background: Query<&mut Sprite, With<BoardBackground>>,
+) {
+    let Some(window) = windows.iter().next() else { return };
+    let board = game.board();
+    let fitted = BoardLayout::fit(board.width(), board.visible_height(), window.size());
+    if layout.set_if_neq(fitted) {
+        for mut sprite in background.iter_mut() {
+            sprite.custom_size = Some(fitted.size());
+        }
+    }
+}
@@ -1,9 +1,19 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use crate::resources::{GameScore, NextPiece};
+use bevy_tetris::engine::MAX_PREVIEWS;
+use crate::components::PieceColor;
+use crate::resources::{GameScore, NextQueue, TetrisGame};
+
+// Vertical space for each of the smaller queue previews
+const QUEUE_SLOT_HEIGHT: f32 = 50.0;
 
 #[derive(Component)]
 pub struct ScoreText;
 
+#[derive(Component)]
+pub struct ClockText;
+
 #[derive(Component)]
 pub struct NextPieceDisplay;
 
@@ -28,6 +38,23 @@ pub fn setup_ui(mut commands: Commands) {
         ScoreText,
     ));
 
+    // Clock
+    commands.spawn((
+        Text::new(format_time(Duration::ZERO)),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(45.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        ClockText,
+    ));
+
     // Next Piece Label
     commands.spawn((
         Text::new("Next:"),
@@ -44,14 +71,14 @@ pub fn setup_ui(mut commands: Commands) {
         },
     ));
     
-    // Next Piece Container
+    // Next Queue Container (first preview full size, the rest stacked smaller below)
     commands.spawn((
         Node {
             position_type: PositionType::Absolute,
             top: Val::Px(90.0),
             right: Val::Px(50.0),
             width: Val::Px(100.0),
-            height: Val::Px(100.0),
+            height: Val::Px(100.0 + (MAX_PREVIEWS - 1) as f32 * QUEUE_SLOT_HEIGHT),
             ..default()
         },
         NextPieceDisplay,
@@ -86,6 +113,34 @@ pub fn setup_ui(mut commands: Commands) {
     ));
 }
 
+/// `m:ss.mmm`, for timers and results.
+pub fn format_time(time: Duration) -> String {
+    let millis = time.as_millis();
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+/// `+s.mmm` / `-s.mmm`: how far `time` is behind (+) or ahead of (-) `reference`.
+pub fn format_delta(time: Duration, reference: Duration) -> String {
+    if time >= reference {
+        format!("+{:.3}", (time - reference).as_secs_f32())
+    } else {
+        format!("-{:.3}", (reference - time).as_secs_f32())
+    }
+}
+
+/// Time played, or time left in modes with a time limit.
+pub fn update_clock(game: Res<TetrisGame>, mut query: Query<&mut Text, With<ClockText>>) {
+    let clock = match game.ruleset().mode.time_limit {
+        Some(limit) => format_time(limit.saturating_sub(game.elapsed())),
+        None => format_time(game.elapsed()),
+    };
+    for mut text in query.iter_mut() {
+        if text.0 != clock {
+            text.0 = clock.clone();
+        }
+    }
+}
+
 pub fn update_score(score: Res<GameScore>, mut query: Query<&mut Text, With<ScoreText>>) {
     if score.is_changed() {
         for mut text in query.iter_mut() {
@@ -96,37 +151,42 @@ pub fn update_score(score: Res<GameScore>, mut query: Query<&mut Text, With<Scor
 
 pub fn update_next_piece(
     mut commands: Commands,
-    next_piece: Res<NextPiece>,
+    next_queue: Res<NextQueue>,
+    game: Res<TetrisGame>,
     query: Query<Entity, With<NextPieceDisplay>>,
     children_query: Query<&Children>,
 ) {
-    if next_piece.is_changed() {
-        if let Some(container_entity) = query.iter().next() {
-            // Despawn existing children
-            if let Ok(children) = children_query.get(container_entity) {
-                for child in children.iter() {
-                    commands.entity(child).despawn();
-                }
+    if next_queue.is_changed() && let Some(container_entity) = query.iter().next() {
+        // Despawn existing children
+        if let Ok(children) = children_query.get(container_entity) {
+            for child in children.iter() {
+                commands.entity(child).despawn();
             }
-            
-            // Spawn new blocks
-            let offsets = next_piece.piece_type.get_offsets(); // Use default rotation
-            let color = next_piece.piece_type.get_color();
-            
+        }
+        
+        for (i, piece_type) in next_queue.pieces.iter().enumerate() {
+            // First preview at the original size, later ones smaller and stacked below
+            let (cell, origin_x, origin_y) = if i == 0 {
+                (20.0, 40.0, 40.0)
+            } else {
+                (12.0, 44.0, 100.0 + (i - 1) as f32 * QUEUE_SLOT_HEIGHT + 20.0)
+            };
+            let offsets = game.ruleset().rotation.system().offsets(*piece_type, 0); // As it will spawn
+            let color = piece_type.get_color();
+
             for (x, y) in offsets.iter() {
                 // Map x, y to UI coordinates
-                // Center is roughly 50, 50
-                let ui_x = 40.0 + (*x as f32 * 20.0);
-                let ui_y = 40.0 - (*y as f32 * 20.0); // Invert y for UI
-                
+                let ui_x = origin_x + (*x as f32 * cell);
+                let ui_y = origin_y - (*y as f32 * cell); // Invert y for UI
+
                 commands.entity(container_entity).with_children(|parent| {
                     parent.spawn((
                         Node {
                             position_type: PositionType::Absolute,
                             left: Val::Px(ui_x),
                             top: Val::Px(ui_y),
-                            width: Val::Px(18.0),
-                            height: Val::Px(18.0),
+                            width: Val::Px(cell - 2.0),
+                            height: Val::Px(cell - 2.0),
                             ..default()
                         },
                         BackgroundColor(color),
@@ -140,43 +200,42 @@ pub fn update_next_piece(
 pub fn update_hold_piece(
     mut commands: Commands,
     hold_piece: Res<crate::resources::HoldPiece>,
+    game: Res<TetrisGame>,
     query: Query<Entity, With<HoldPieceDisplay>>,
     children_query: Query<&Children>,
 ) {
-    if hold_piece.is_changed() {
-        if let Some(container_entity) = query.iter().next() {
-            // Despawn existing children
-            if let Ok(children) = children_query.get(container_entity) {
-                for child in children.iter() {
-                    commands.entity(child).despawn();
-                }
+    if hold_piece.is_changed() && let Some(container_entity) = query.iter().next() {
+        // Despawn existing children
+        if let Ok(children) = children_query.get(container_entity) {
+            for child in children.iter() {
+                commands.entity(child).despawn();
             }
+        }
+        
+        if let Some(piece_type) = hold_piece.piece_type {
+            // Spawn new blocks
+            let offsets = game.ruleset().rotation.system().offsets(piece_type, 0); // As it will spawn
+            let color = if hold_piece.can_hold { piece_type.get_color() } else { piece_type.get_color().with_alpha(0.5) };
             
-            if let Some(piece_type) = hold_piece.piece_type {
-                // Spawn new blocks
-                let offsets = piece_type.get_offsets(); // Use default rotation
-                let color = if hold_piece.can_hold { piece_type.get_color() } else { piece_type.get_color().with_alpha(0.5) };
+            for (x, y) in offsets.iter() {
+                // Map x, y to UI coordinates
+                // Center is roughly 50, 50
+                let ui_x = 40.0 + (*x as f32 * 20.0);
+                let ui_y = 40.0 - (*y as f32 * 20.0); // Invert y for UI
                 
-                for (x, y) in offsets.iter() {
-                    // Map x, y to UI coordinates
-                    // Center is roughly 50, 50
-                    let ui_x = 40.0 + (*x as f32 * 20.0);
-                    let ui_y = 40.0 - (*y as f32 * 20.0); // Invert y for UI
-                    
-                    commands.entity(container_entity).with_children(|parent| {
-                        parent.spawn((
-                            Node {
-                                position_type: PositionType::Absolute,
-                                left: Val::Px(ui_x),
-                                top: Val::Px(ui_y),
-                                width: Val::Px(18.0),
-                                height: Val::Px(18.0),
-                                ..default()
-                            },
-                            BackgroundColor(color),
-                        ));
-                    });
-                }
+                commands.entity(container_entity).with_children(|parent| {
+                    parent.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(ui_x),
+                            top: Val::Px(ui_y),
+                            width: Val::Px(18.0),
+                            height: Val::Px(18.0),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                    ));
+                });
             }
         }
     }